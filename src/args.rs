@@ -1,11 +1,15 @@
+use chrono::Weekday;
 use clap::{Args, ValueEnum};
 
+use dinner_generator::generate::Strategy;
+use dinner_generator::settings::Settings;
+
 #[derive(Args, Debug, Clone)]
 pub struct FileArgs {
-    /// input file which contains all possible options
+    /// input file which contains all possible options, overrides the config file
     #[arg(short, long, value_name = "FILENAME")]
     input_file: Option<String>,
-    /// output file to write the <day count> amount of options to
+    /// output file to write the <day count> amount of options to, overrides the config file
     #[arg(short, long, value_name = "FILENAME")]
     output_file: Option<String>,
 }
@@ -19,15 +23,139 @@ impl FileArgs {
     }
 }
 
+#[derive(Args, Debug, Clone)]
+pub struct PlanArgs {
+    /// Amount of days to plan for, days on weekdays which are not planned do not count
+    #[arg(short, long, value_name = "COUNT")]
+    days: Option<usize>,
+    /// Weekdays to plan for, e.g. mon,tue,wed,thu,fri
+    #[arg(long, value_name = "WEEKDAYS", value_delimiter = ',')]
+    weekdays: Option<Vec<Weekday>>,
+    /// Day on which the week starts
+    #[arg(long, value_name = "WEEKDAY")]
+    week_start: Option<Weekday>,
+    /// Read seasons, optional components and variants from parentheses in recipe names
+    #[arg(long, default_value_t = false)]
+    qualifiers: bool,
+    /// Amount of days before a planned recipe may be picked again
+    #[arg(long, value_name = "DAYS")]
+    cooldown: Option<i64>,
+}
+
+impl PlanArgs {
+    /// The given settings with the passed values applied
+    pub fn settings(&self, settings: &Settings) -> Settings {
+        let mut settings = settings.clone();
+        if let Some(days) = self.days {
+            settings.set_horizon(days);
+        }
+        if let Some(weekdays) = &self.weekdays {
+            settings.set_weekdays(weekdays.clone());
+        }
+        if let Some(week_start) = self.week_start {
+            settings.set_week_start(week_start);
+        }
+        if self.qualifiers {
+            settings.set_qualifiers(true);
+        }
+        if let Some(cooldown) = self.cooldown {
+            settings.set_cooldown_days(cooldown);
+        }
+        settings
+    }
+}
+
+/// How `show` prints the plan
+#[derive(ValueEnum, Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Format {
+    /// Aligned columns
+    #[default]
+    Table,
+    Json,
+    Csv,
+    Markdown,
+    /// Tab separated lines
+    Plain,
+}
+
 #[derive(Args, Debug)]
 pub struct ShowArgs {
-    #[arg(short, long)]
-    output_path: String,
+    #[command(flatten)]
+    file_args: FileArgs,
+    /// Show the chance of each pool recipe to be picked instead
+    #[arg(long, default_value_t = false)]
+    odds: bool,
+    #[arg(short, long, value_enum, default_value_t = Format::Table)]
+    format: Format,
+    /// Also list the recipes in the pool
+    #[arg(long, default_value_t = false)]
+    pool: bool,
+    /// Include the recipe links
+    #[arg(long, default_value_t = false)]
+    links: bool,
+    /// Include the recipe tags
+    #[arg(long, default_value_t = false)]
+    tags: bool,
+    #[command(flatten)]
+    plan_args: PlanArgs,
 }
 
 impl ShowArgs {
-    pub fn output_path(&self) -> &str {
-        &self.output_path
+    pub fn file_args(&self) -> &FileArgs {
+        &self.file_args
+    }
+    pub fn odds(&self) -> bool {
+        self.odds
+    }
+    pub fn format(&self) -> Format {
+        self.format
+    }
+    pub fn pool(&self) -> bool {
+        self.pool
+    }
+    pub fn links(&self) -> bool {
+        self.links
+    }
+    pub fn tags(&self) -> bool {
+        self.tags
+    }
+    pub fn plan_args(&self) -> &PlanArgs {
+        &self.plan_args
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct ShuffleArgs {
+    #[command(flatten)]
+    file_args: FileArgs,
+    /// Seed for the new order, to recreate an earlier shuffle
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Keep today's dinner where it is
+    #[arg(short, long, default_value_t = false)]
+    keep_today: bool,
+    /// Avoid recipes sharing a tag on consecutive days
+    #[arg(short, long, default_value_t = false)]
+    spread_tags: bool,
+    #[command(flatten)]
+    plan_args: PlanArgs,
+}
+
+impl ShuffleArgs {
+    pub fn file_args(&self) -> &FileArgs {
+        &self.file_args
+    }
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+    pub fn keep_today(&self) -> bool {
+        self.keep_today
+    }
+    pub fn spread_tags(&self) -> bool {
+        self.spread_tags
+    }
+    pub fn plan_args(&self) -> &PlanArgs {
+        &self.plan_args
     }
 }
 
@@ -35,24 +163,56 @@ impl ShowArgs {
 pub struct GenerateArgs {
     #[command(flatten)]
     file_args: FileArgs,
-    /// Amount of days to generate for (amount of entries in output file)
-    #[arg(short, long, value_name = "COUNT")]
-    days: Option<usize>,
-    /// Whether to reinitialise (clear output and start anew)
+    #[command(flatten)]
+    plan_args: PlanArgs,
+    /// Whether to reinitialise (clear output and start anew), always done if set in the config
     #[arg(short, long, default_value_t = false)]
     reset: bool,
+    /// How to pick recipes from the pool
+    #[arg(short, long, value_enum, default_value_t = Strategy::Random)]
+    strategy: Strategy,
+    /// Seed for the random picks, to recreate an earlier plan. Only does so together with
+    /// --reset, as otherwise the recipes of the current plan are left out of the pool
+    /// and only while no days were regenerated, shuffled or filled since, as these store their own
+    /// seed
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Only pick recipes for unplanned days, keeping all others
+    #[arg(short, long, default_value_t = false)]
+    fill: bool,
+    /// Only pick recipes with one of these tags
+    #[arg(long, value_name = "TAG", value_delimiter = ',')]
+    include_tag: Vec<String>,
+    /// Never pick recipes with one of these tags
+    #[arg(long, value_name = "TAG", value_delimiter = ',')]
+    exclude_tag: Vec<String>,
 }
 
 impl GenerateArgs {
     pub fn file_args(&self) -> &FileArgs {
         &self.file_args
     }
-    pub fn days(&self) -> Option<usize> {
-        self.days
+    pub fn plan_args(&self) -> &PlanArgs {
+        &self.plan_args
     }
     pub fn reset(&self) -> bool {
         self.reset
     }
+    pub fn strategy(&self) -> Strategy {
+        self.strategy
+    }
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+    pub fn fill(&self) -> bool {
+        self.fill
+    }
+    pub fn include_tag(&self) -> &Vec<String> {
+        &self.include_tag
+    }
+    pub fn exclude_tag(&self) -> &Vec<String> {
+        &self.exclude_tag
+    }
 }
 
 #[derive(Args, Debug)]
@@ -65,10 +225,18 @@ pub struct EditArgs {
         ("edit_field", "add-to-input"),
         ("edit_field", "swap-entry"),
         ("edit_field", "remove-from-input"),
+        ("edit_field", "enable"),
         ("edit_field", "input-entry"),
+        ("edit_field", "weight"),
+        ("edit_field", "tags"),
+        ("edit_field", "lock"),
+        ("edit_field", "unlock"),
+        ("edit_field", "slot"),
         ("edit_field", "entry")
     ]))]
     entry: Option<Vec<String>>,
+    #[command(flatten)]
+    plan_args: PlanArgs,
 }
 
 impl EditArgs {
@@ -82,14 +250,42 @@ impl EditArgs {
     pub fn entry(&self) -> Option<&Vec<String>> {
         self.entry.as_ref()
     }
+    pub fn plan_args(&self) -> &PlanArgs {
+        &self.plan_args
+    }
 }
 
+/// What to edit. Days are given as an index, `today`, `tomorrow`, a date like `2026-10-20`, a
+/// weekday like `tue` or the recipe planned on it.
 #[derive(ValueEnum, Copy, Clone, Debug)]
 pub enum EditType {
     AddToInput,
     RemoveFromInput,
+    /// Re-enable a recipe which was removed from the input
+    Enable,
     InputEntry,
+    /// Set the weight of an input entry: favourite, normal, rarely or a number
+    Weight,
+    /// Set the tags of an input entry, separated by commas
+    Tags,
     Entry,
     RegenEntry,
     SwapEntry,
+    /// Keep a day when regenerating or shuffling
+    Lock,
+    Unlock,
+    /// Mark a day as skip, eat-out, leftovers or unplanned
+    Slot,
+}
+
+#[derive(Args, Debug)]
+pub struct LintArgs {
+    #[command(flatten)]
+    file_args: FileArgs,
+}
+
+impl LintArgs {
+    pub fn file_args(&self) -> &FileArgs {
+        &self.file_args
+    }
 }
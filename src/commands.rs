@@ -1,152 +1,363 @@
-use crate::args::{EditArgs, EditType, FileArgs, GenerateArgs, ShowArgs};
-use crate::file_utils::read_file;
-use crate::generate::{Collection, Generate};
-use clap::Parser;
-use std::io;
+use crate::args::{
+    EditArgs, EditType, FileArgs, GenerateArgs, LintArgs, PlanArgs, ShowArgs, ShuffleArgs,
+};
+#[cfg(feature = "gui")]
+use crate::gui;
+use crate::render::{self, Columns};
+#[cfg(feature = "tui")]
+use crate::tui;
+#[cfg(not(feature = "gui"))]
+use clap::CommandFactory;
+use clap::{Parser, Subcommand, ValueEnum};
+use dinner_generator::config::Config;
+use dinner_generator::document::InputDocument;
+use dinner_generator::error::{Error, Result};
+use dinner_generator::generate::{Collection, Generate};
+use dinner_generator::qualifier;
+use dinner_generator::recipe::{self, parse_weight};
+use dinner_generator::slot::SlotKind;
+use std::io::{self, IsTerminal, Write};
+use std::process::ExitCode;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
+pub struct Cli {
+    /// Opens the frontend if left out, prints this help if built without it
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
 pub enum Command {
+    #[cfg(feature = "gui")]
+    #[command(name = "gui", about = "Open the frontend")]
+    Gui,
+    #[cfg(feature = "tui")]
+    #[command(name = "tui", about = "Open the frontend in the terminal")]
+    Tui,
     #[command(name = "show", about = "Show the current entries")]
     Show(ShowArgs),
     #[command(name = "edit", about = "Edit an entry or the input")]
     Edit(EditArgs),
     #[command(name = "shuffle", about = "Shuffle the order of the current entries")]
-    Shuffle(FileArgs),
-    #[command(name = "generate", about = "Shuffle the order of the current entries")]
+    Shuffle(ShuffleArgs),
+    #[command(name = "generate", about = "Generate new entries")]
     Generate(GenerateArgs),
+    #[command(
+        name = "lint",
+        about = "Show how the parentheses in the input are interpreted"
+    )]
+    Lint(LintArgs),
+}
+
+/// Input file passed on the command line, or the one from the config
+fn input_file(file_args: &FileArgs, config: &Config) -> String {
+    file_args
+        .input_file()
+        .unwrap_or(config.input_file().to_owned())
+}
+
+/// Output file passed on the command line, or the one from the config
+fn output_file(file_args: &FileArgs, config: &Config) -> String {
+    file_args
+        .output_file()
+        .unwrap_or(config.output_file().to_owned())
+}
+
+fn prepare_generate(
+    file_args: &FileArgs,
+    plan_args: &PlanArgs,
+    config: &Config,
+    reset: bool,
+) -> Result<Generate> {
+    let inputfile = &input_file(file_args, config);
+
+    let outputfile = &output_file(file_args, config);
+    Generate::read_entries(
+        inputfile,
+        outputfile,
+        plan_args.settings(config.plan()),
+        reset,
+    )
+}
+
+/// Drops the days which have passed, telling how many
+fn rotate(generate: &mut Generate) {
+    let past = generate.rotate();
+    if past > 0 {
+        eprintln!("Rotating out {} past day(s)", past);
+    }
+}
+
+/// Tells which recipes were picked despite their cooldown by the last selection
+fn report_relaxed(generate: &Generate) {
+    if !generate.relaxed().is_empty() {
+        eprintln!(
+            "Pool too small, relaxed cooldown for: {:?}",
+            generate.relaxed()
+        );
+    }
 }
 
-fn prepare_generate(file_args: &FileArgs, days: usize, reset: bool) -> Result<Generate, io::Error> {
-    let inputfile = &file_args.input_file().unwrap_or("input.txt".to_owned());
+fn print_output(generate: &Generate) {
+    println!("seed: {}", generate.seed());
+    for (idx, entry) in generate.days().iter().enumerate() {
+        let date = generate.date_of(idx);
+        if idx > 0 && generate.settings().starts_week(date) {
+            println!("--");
+        }
+        let date = date.format("%a %-d %b");
+        match entry.recipe().and_then(|r| r.source()) {
+            Some(source) => println!("{}: {}: {} ({})", idx, date, entry, source),
+            None => println!("{}: {}: {}", idx, date, entry),
+        }
+    }
+}
 
-    let outputfile = &file_args.output_file().unwrap_or("output.txt".to_owned());
-    Generate::read_entries(inputfile, outputfile, days, reset)
+fn generate(args: GenerateArgs, config: &Config) -> Result<()> {
+    let reset = args.reset() || config.reset();
+    if reset {
+        eprintln!("Resetting output");
+    }
+    let mut generate = prepare_generate(args.file_args(), args.plan_args(), config, reset)?;
+    rotate(&mut generate);
+    generate.set_strategy(args.strategy());
+    generate.set_tag_filter(args.include_tag().clone(), args.exclude_tag().clone());
+    if let Some(seed) = args.seed() {
+        generate.set_seed(seed);
+    }
+    let mut generate = if args.fill() {
+        generate.fill_unplanned();
+        generate
+    } else {
+        generate.generate_days(true)
+    };
+    report_relaxed(&generate);
+    print_output(&generate);
+    generate.write_file(&output_file(args.file_args(), config))
 }
 
-fn generate(args: GenerateArgs) {
-    let days = args.days().unwrap_or(7_usize);
-    let mut generate = prepare_generate(args.file_args(), days, args.reset()).unwrap();
-    let _ = generate.generate_days(days, false);
-    generate.print_output();
-    let _ = generate.write_file(
-        &args
-            .file_args()
-            .output_file()
-            .unwrap_or("output.txt".to_owned()),
-    );
+/// Returns the value at `index` of the entries passed with `-e`
+fn entry_value<'a>(entry: &'a [String], index: usize, expected: &str) -> Result<&'a str> {
+    entry
+        .get(index)
+        .map(String::as_str)
+        .ok_or_else(|| Error::Usage(format!("Expected {}", expected)))
 }
 
-fn edit(args: EditArgs) {
-    println!("Edit: {:?}", args);
+/// Asks which entry was meant when a lookup was ambiguous, if there is a terminal to ask on
+fn choose(found: Result<usize>) -> Result<usize> {
+    let Err(Error::Ambiguous { query, candidates }) = found else {
+        return found;
+    };
+    if !io::stdin().is_terminal() {
+        return Err(Error::Ambiguous { query, candidates });
+    }
+    ask(&query, &candidates, false)?
+        .ok_or_else(|| Error::Usage(format!("No entry chosen for {}", query)))
+}
 
-    let mut generate = prepare_generate(args.file_args(), 0, false).unwrap();
-    generate.print_output();
+/// Asks which of the candidates was meant, with `as_typed` also offering the query itself, in
+/// which case None is returned when it is chosen
+fn ask(query: &str, candidates: &[(usize, String)], as_typed: bool) -> Result<Option<usize>> {
+    println!("{} could mean:", query);
+    for (number, (_, name)) in candidates.iter().enumerate() {
+        println!("{}: {}", number + 1, name);
+    }
+    if as_typed {
+        println!("{}: {} as typed", candidates.len() + 1, query);
+    }
+    print!("Which one? ");
+    io::stdout().flush().map_err(Error::io("stdout"))?;
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .map_err(Error::io("stdin"))?;
+    match answer
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|number| number.checked_sub(1))
+    {
+        Some(number) if number < candidates.len() => Ok(Some(candidates[number].0)),
+        Some(number) if as_typed && number == candidates.len() => Ok(None),
+        _ => Err(Error::Usage(format!("No entry chosen for {}", query))),
+    }
+}
+
+fn edit(args: EditArgs, config: &Config) -> Result<()> {
+    let mut generate = prepare_generate(args.file_args(), args.plan_args(), config, false)?;
+    rotate(&mut generate);
+    let entry: &[String] = args.entry().map_or(&[], |entry| entry.as_slice());
     match args.edit_field() {
         EditType::AddToInput => {
-            for i in args.entry().expect("Expected a value for entry to add!") {
-                generate.add_to_pool(i.to_string());
+            for i in entry {
+                generate.add_to_pool(i.to_string())?;
             }
         }
         EditType::RemoveFromInput => {
-            for i in args
-                .entry()
-                .expect("Expected a value for entries to remove!")
-            {
-                let _ = generate
-                    .remove_from_pool_by_strvalue(i, &args.file_args().input_file().unwrap());
+            for i in entry {
+                let index = choose(generate.find_exact_entry(Collection::Pool, i))?;
+                generate.remove_from_pool(index)?;
+            }
+        }
+        EditType::Enable => {
+            for i in entry {
+                let index = choose(generate.find_entry(Collection::Archive, i))?;
+                generate.restore_from_archive(index)?;
             }
         }
         EditType::Entry => {
-            let _ = generate.edit_days_entry(
-                generate
-                    .find_entry(
-                        Collection::Days,
-                        &args.entry().expect("Please pass an entry to edit")[0],
-                    )
-                    .unwrap_or_else(|| {
-                        panic!(
-                            "Passed entry {} does not exist in current entries pool",
-                            args.entry().unwrap()[0]
-                        )
-                    }),
-                args.entry().unwrap_or_else(|| {
-                    panic!(
-                        "Please pass a new value for entry {}",
-                        args.entry().unwrap()[0]
-                    )
-                })[1]
-                    .to_owned(),
-            );
+            let index = choose(generate.find_day(entry_value(entry, 0, "a day to edit")?))?;
+            let new_entry = entry_value(entry, 1, "a new value for the day")?;
+            match generate.plan_entry(index, new_entry) {
+                Err(Error::Ambiguous { query, candidates }) if io::stdin().is_terminal() => {
+                    match ask(&query, &candidates, true)? {
+                        Some(pool_index) => generate.plan_pool_entry(index, pool_index)?,
+                        None => generate.edit_days_entry(index, new_entry.to_owned())?,
+                    }
+                }
+                planned => planned?,
+            }
         }
         EditType::RegenEntry => {
-            let _ = generate.regenerate_entry(
-                generate
-                    .find_entry(Collection::Days, &args.entry().unwrap()[0])
-                    .expect("Entry not found"),
-            );
+            let index = choose(generate.find_day(entry_value(entry, 0, "a day to regenerate")?))?;
+            eprintln!("Removed entry: {}", generate.days()[index]);
+            generate.regenerate_entry(index)?;
+            report_relaxed(&generate);
         }
         EditType::SwapEntry => {
-            let old_pos = generate
-                .find_entry(
-                    Collection::Days,
-                    &args
-                        .entry()
-                        .expect("Please pass an existing entry to be swapped")[0],
-                )
-                .unwrap_or_else(|| {
-                    panic!(
-                        "Passed entry {} does not exist in entries pool",
-                        args.entry().unwrap()[0]
-                    )
-                });
-            let new_pos = generate
-                .find_entry(
-                    Collection::Days,
-                    &args.entry().expect("Please pass an existing entry to swap")[1],
-                )
-                .unwrap_or_else(|| {
-                    panic!(
-                        "Passed entry {} does not exist in entries pool",
-                        args.entry().unwrap()[0]
-                    )
-                });
-            let _ = generate.swap_days_entries(old_pos, new_pos);
+            let old_pos = choose(generate.find_day(entry_value(entry, 0, "two days to swap")?))?;
+            let new_pos = choose(generate.find_day(entry_value(entry, 1, "two days to swap")?))?;
+            generate.swap_days_entries(old_pos, new_pos)?;
+        }
+        EditType::Weight => {
+            let name = entry_value(entry, 0, "an entry and a weight")?;
+            let weight = entry_value(entry, 1, "a weight for the entry")?;
+            let weight = parse_weight(weight)
+                .ok_or_else(|| Error::Parse(format!("Invalid weight {}", weight)))?;
+            let index = choose(generate.find_exact_entry(Collection::Pool, name))?;
+            generate.set_pool_weight(index, weight)?;
+        }
+        EditType::Tags => {
+            let name = entry_value(entry, 0, "an entry and its tags")?;
+            let tags = entry.get(1).map_or(vec![], |tags| recipe::parse_tags(tags));
+            let index = choose(generate.find_exact_entry(Collection::Pool, name))?;
+            generate.set_pool_tags(index, tags)?;
+        }
+        EditType::Lock | EditType::Unlock => {
+            let locked = matches!(args.edit_field(), EditType::Lock);
+            for i in entry {
+                let index = choose(generate.find_day(i))?;
+                generate.set_locked(index, locked)?;
+            }
+        }
+        EditType::Slot => {
+            let day = entry_value(entry, 0, "a day and what to mark it as")?;
+            let kind = entry_value(entry, 1, "skip, eat-out, leftovers or unplanned")?;
+            let kind = SlotKind::from_str(kind, true).map_err(Error::Parse)?;
+            let index = choose(generate.find_day(day))?;
+            generate.set_day_slot(index, kind)?;
         }
         EditType::InputEntry => {
-            let _ = generate.edit_pool_entry_by_strvalue(
-                &args.entry().expect("Pool entry to edit not found")[0],
-                args.entry().expect("Expected a value for pool entry")[1].to_owned(),
-            );
+            let old_entry = entry_value(entry, 0, "a pool entry to edit")?;
+            let new_entry = entry_value(entry, 1, "a new value for the pool entry")?;
+            let index = choose(generate.find_exact_entry(Collection::Pool, old_entry))?;
+            generate.edit_pool_entry(index, new_entry.to_owned())?;
         }
     }
+    generate.write_file(&output_file(args.file_args(), config))?;
+    print_output(&generate);
+    Ok(())
 }
 
-fn shuffle(args: FileArgs) {
-    println!("Shuffle: {:?}", args);
+fn shuffle(args: ShuffleArgs, config: &Config) -> Result<()> {
+    let mut generate = prepare_generate(args.file_args(), args.plan_args(), config, false)?;
+    rotate(&mut generate);
+    if let Some(seed) = args.seed() {
+        generate.set_seed(seed);
+    }
+    let clashes = generate.randomize_days(args.keep_today(), args.spread_tags());
+    if args.spread_tags() && clashes > 0 {
+        eprintln!(
+            "Could not avoid {} day(s) sharing a tag with the next",
+            clashes
+        );
+    }
+    print_output(&generate);
+    generate.write_file(&output_file(args.file_args(), config))
 }
 
-fn show(args: ShowArgs) {
-    let outputfile = &args.output_path();
-
-    println!("{:?}", read_file(outputfile));
+fn show(args: ShowArgs, config: &Config) -> Result<()> {
+    let generate = prepare_generate(args.file_args(), args.plan_args(), config, false)?;
+    if args.odds() {
+        let mut odds = generate.odds();
+        odds.sort_by(|a, b| b.1.total_cmp(&a.1));
+        for (entry, chance) in odds {
+            println!(
+                "{:>6.2}% {} ({})",
+                chance * 100.0,
+                entry,
+                recipe::weight_label(entry.weight())
+            );
+        }
+    } else {
+        let columns = Columns {
+            links: args.links(),
+            tags: args.tags(),
+        };
+        print!(
+            "{}",
+            render::render(&generate, args.format(), args.pool(), columns)
+        );
+    }
+    Ok(())
 }
 
-pub fn run() {
-    let args = Command::parse();
+/// Lists every entry of the input with the meaning of its parentheses, regardless of whether
+/// the `qualifiers` setting is on
+fn lint(args: LintArgs, config: &Config) -> Result<()> {
+    let input = InputDocument::load(&input_file(args.file_args(), config))?;
+    let mut recipes = input.recipes();
+    let mut archive = input.disabled_recipes();
+    qualifier::apply(recipes.iter_mut().chain(archive.iter_mut()));
+    print!("{}", render::lint(&recipes, &archive));
+    Ok(())
+}
 
-    match args {
-        Command::Show(show_args) => {
-            show(show_args);
-        }
-        Command::Shuffle(shuffle_args) => {
-            shuffle(shuffle_args);
-        }
-        Command::Edit(edit_args) => {
-            edit(edit_args);
+/// Runs the command line, printing errors and returning a failure exit code on errors
+pub fn run() -> ExitCode {
+    let args = Cli::parse();
+    let command = match args.command {
+        Some(command) => command,
+        #[cfg(feature = "gui")]
+        None => Command::Gui,
+        #[cfg(not(feature = "gui"))]
+        None => {
+            let _ = Cli::command().print_help();
+            return ExitCode::SUCCESS;
         }
-        Command::Generate(generate_args) => {
-            generate(generate_args);
+    };
+    match run_command(command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
         }
     }
 }
+
+fn run_command(command: Command) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    match command {
+        #[cfg(feature = "gui")]
+        Command::Gui => gui::run()?,
+        #[cfg(feature = "tui")]
+        Command::Tui => tui::run(Config::load()?)?,
+        Command::Show(show_args) => show(show_args, &Config::load()?)?,
+        Command::Shuffle(shuffle_args) => shuffle(shuffle_args, &Config::load()?)?,
+        Command::Edit(edit_args) => edit(edit_args, &Config::load()?)?,
+        Command::Generate(generate_args) => generate(generate_args, &Config::load()?)?,
+        Command::Lint(lint_args) => lint(lint_args, &Config::load()?)?,
+    }
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::settings::Settings;
+
+const APP_DIR: &str = "dinner_generator";
+const CONFIG_FILE: &str = "config.toml";
+
+/// Settings read from `config.toml` in the user's config directory, shared by the CLI and GUI.
+/// Paths default to the user's data directory so the app works from any working directory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    input_file: String,
+    output_file: String,
+    /// Whether `generate` starts a new plan by default, as if `--reset` was passed
+    reset: bool,
+    plan: Settings,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            input_file: Self::data_path("input.txt"),
+            output_file: Self::data_path("output.txt"),
+            reset: false,
+            plan: Settings::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Location of the config file, None if the platform has no config directory
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(APP_DIR).join(CONFIG_FILE))
+    }
+
+    /// Reads the config file, writing the defaults to it if it does not exist yet
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            let config = Self::default();
+            config.save()?;
+            return Ok(config);
+        }
+        let file = path.to_string_lossy();
+        let content = fs::read_to_string(&path).map_err(Error::io(&file))?;
+        toml::from_str(&content).map_err(|e| Error::Config(format!("{}: {}", file, e.message())))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        let file = path.to_string_lossy();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(Error::io(&file))?;
+        }
+        let content = toml::to_string(self).map_err(|e| Error::Config(e.to_string()))?;
+        fs::write(&path, content).map_err(Error::io(&file))
+    }
+
+    /// Creates the directories of the data files and an empty input file, so a first launch
+    /// has something to open
+    pub fn prepare_files(&self) -> Result<()> {
+        for file in [&self.input_file, &self.output_file] {
+            if let Some(dir) = Path::new(file).parent() {
+                fs::create_dir_all(dir).map_err(Error::io(file))?;
+            }
+        }
+        if !Path::new(&self.input_file).exists() {
+            fs::write(&self.input_file, "").map_err(Error::io(&self.input_file))?;
+        }
+        Ok(())
+    }
+
+    pub fn input_file(&self) -> &str {
+        &self.input_file
+    }
+
+    pub fn output_file(&self) -> &str {
+        &self.output_file
+    }
+
+    pub fn reset(&self) -> bool {
+        self.reset
+    }
+
+    pub fn plan(&self) -> &Settings {
+        &self.plan
+    }
+
+    pub fn set_plan(&mut self, plan: Settings) {
+        self.plan = plan;
+    }
+
+    /// Path of a file in the user's data directory, or in the working directory if there is none
+    fn data_path(file: &str) -> String {
+        match dirs::data_dir() {
+            Some(dir) => dir.join(APP_DIR).join(file).to_string_lossy().into_owned(),
+            None => file.to_owned(),
+        }
+    }
+}
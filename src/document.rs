@@ -0,0 +1,410 @@
+use std::fs;
+
+use crate::error::{Error, Result};
+use crate::file_utils;
+use crate::recipe::{self, Recipe, DEFAULT_WEIGHT};
+
+/// A single line of the input file, kept verbatim so the file can be written back unchanged
+#[derive(Debug, Clone)]
+enum Line {
+    Entry {
+        id: usize,
+        text: String,
+    },
+    /// An entry which was commented out, `text` excludes the leading `#`
+    Disabled {
+        id: usize,
+        text: String,
+    },
+    Comment(String),
+    Blank(String),
+}
+
+/// In-memory model of the input file which preserves comments, blank lines, disabled entries
+/// and ordering when recipes are added, edited or removed
+#[derive(Debug, Clone)]
+pub struct InputDocument {
+    path: String,
+    lines: Vec<Line>,
+}
+
+impl InputDocument {
+    pub fn load(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(Error::io(path))?;
+        Ok(Self::parse(path, &content))
+    }
+
+    pub fn parse(path: &str, content: &str) -> Self {
+        let mut next_id = 0;
+        let lines = content
+            .lines()
+            .map(|line| {
+                if line.trim().is_empty() {
+                    Line::Blank(line.to_owned())
+                } else if let Some(text) = disabled_entry(line) {
+                    next_id += 1;
+                    Line::Disabled {
+                        id: next_id - 1,
+                        text: text.to_owned(),
+                    }
+                } else if line.starts_with('#') {
+                    Line::Comment(line.to_owned())
+                } else {
+                    next_id += 1;
+                    Line::Entry {
+                        id: next_id - 1,
+                        text: line.to_owned(),
+                    }
+                }
+            })
+            .collect();
+        Self {
+            path: path.to_owned(),
+            lines,
+        }
+    }
+
+    /// Builds the recipes of all active entries
+    pub fn recipes(&self) -> Vec<Recipe> {
+        self.collect_recipes(false)
+    }
+
+    /// Builds the recipes of all commented out entries
+    pub fn disabled_recipes(&self) -> Vec<Recipe> {
+        self.collect_recipes(true)
+    }
+
+    /// A comment line holding a URL directly below an entry (e.g. `# https://...`) is attached to
+    /// that entry as its source link.
+    fn collect_recipes(&self, disabled: bool) -> Vec<Recipe> {
+        let mut recipes: Vec<Recipe> = Vec::new();
+        let mut attach_to_last = false;
+
+        for line in &self.lines {
+            match line {
+                Line::Entry { id, text } if !disabled => {
+                    recipes.push(parse_entry(*id, text));
+                    attach_to_last = true;
+                }
+                Line::Disabled { id, text } if disabled => {
+                    recipes.push(parse_entry(*id, text));
+                    attach_to_last = true;
+                }
+                Line::Entry { .. } | Line::Disabled { .. } => attach_to_last = false,
+                Line::Comment(text) => match url_comment(text) {
+                    Some(url) if attach_to_last => {
+                        if let Some(recipe) = recipes.last_mut() {
+                            if recipe.source().is_none() {
+                                recipe.set_source(Some(url.to_owned()));
+                            }
+                        }
+                    }
+                    Some(_) => (),
+                    None => attach_to_last = false,
+                },
+                Line::Blank(_) => (),
+            }
+        }
+
+        recipes
+    }
+
+    /// Appends a new entry for the recipe, followed by its source link if it has one
+    pub fn add(&mut self, recipe: &Recipe) {
+        self.lines.push(Line::Entry {
+            id: recipe.id(),
+            text: format_entry(recipe),
+        });
+        if let Some(source) = recipe.source() {
+            self.lines.push(Line::Comment(format!("#{}", source)));
+        }
+    }
+
+    /// Rewrites the entry belonging to the recipe, returns false if it is not in the document
+    pub fn update(&mut self, recipe: &Recipe) -> bool {
+        match self.find(recipe.id()) {
+            Some(pos) => {
+                let id = recipe.id();
+                let text = format_entry(recipe);
+                self.lines[pos] = match self.lines[pos] {
+                    Line::Disabled { .. } => Line::Disabled { id, text },
+                    _ => Line::Entry { id, text },
+                };
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Comments out the entry belonging to the recipe, returns false if it is not in the document
+    pub fn disable(&mut self, id: usize) -> bool {
+        match self.find(id) {
+            Some(pos) => {
+                if let Line::Entry { text, .. } = &self.lines[pos] {
+                    self.lines[pos] = Line::Disabled {
+                        id,
+                        text: text.to_owned(),
+                    };
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Uncomments the entry belonging to the recipe, returns false if it is not in the document
+    pub fn enable(&mut self, id: usize) -> bool {
+        match self.find(id) {
+            Some(pos) => {
+                if let Line::Disabled { text, .. } = &self.lines[pos] {
+                    self.lines[pos] = Line::Entry {
+                        id,
+                        text: text.to_owned(),
+                    };
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let lines: Vec<String> = self
+            .lines
+            .iter()
+            .map(|line| match line {
+                Line::Entry { text, .. } | Line::Comment(text) | Line::Blank(text) => {
+                    text.to_owned()
+                }
+                Line::Disabled { text, .. } => format!("#{}", text),
+            })
+            .collect();
+        file_utils::write_file(&lines, &self.path)
+    }
+
+    /// Id for a new entry, after all entries in the document including those whose recipes are
+    /// no longer in the pool, e.g. because they are planned
+    pub fn next_id(&self) -> usize {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                Line::Entry { id, .. } | Line::Disabled { id, .. } => Some(id + 1),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Whether the recipe with the given id is an enabled entry of the document
+    pub fn contains(&self, id: usize) -> bool {
+        self.lines
+            .iter()
+            .any(|line| matches!(line, Line::Entry { id: entry, .. } if *entry == id))
+    }
+
+    fn find(&self, id: usize) -> Option<usize> {
+        self.lines.iter().position(|line| match line {
+            Line::Entry { id: line_id, .. } | Line::Disabled { id: line_id, .. } => *line_id == id,
+            _ => false,
+        })
+    }
+}
+
+/// Reads an entry line into a recipe. Trailing markers after the name hold metadata, in any
+/// order: `*favourite`, `*rarely` or `*<number>` set the selection weight, `[vegetarian, quick]`
+/// the tags and `@20min` or `@1h` the preparation time. A line without markers is just a name.
+pub(crate) fn parse_entry(id: usize, text: &str) -> Recipe {
+    let mut name = text.trim_end();
+    let mut weight = DEFAULT_WEIGHT;
+    let mut tags = vec![];
+    let mut prep_time = None;
+
+    loop {
+        if let Some(rest) = name.strip_suffix(']') {
+            match rest.rsplit_once('[') {
+                Some((rest, list)) if !rest.trim().is_empty() => {
+                    tags = recipe::parse_tags(list);
+                    name = rest.trim_end();
+                    continue;
+                }
+                _ => break,
+            }
+        }
+        let Some((rest, marker)) = name.rsplit_once(' ') else {
+            break;
+        };
+        if let Some(value) = marker.strip_prefix('*').and_then(recipe::parse_weight) {
+            weight = value;
+        } else if let Some(value) = marker.strip_prefix('@').and_then(recipe::parse_prep_time) {
+            prep_time = Some(value);
+        } else {
+            break;
+        }
+        name = rest.trim_end();
+    }
+
+    let mut recipe = Recipe::new(id, name.to_owned());
+    recipe.set_weight(weight);
+    recipe.set_tags(tags);
+    recipe.set_prep_time(prep_time);
+    recipe
+}
+
+/// Formats a recipe as an entry line which `parse_entry` reads back
+fn format_entry(recipe: &Recipe) -> String {
+    let mut text = recipe.name().to_owned();
+    if !recipe.tags().is_empty() {
+        text.push_str(&format!(" [{}]", recipe.tags().join(", ")));
+    }
+    if let Some(minutes) = recipe.prep_time() {
+        text.push_str(&format!(" @{}min", minutes));
+    }
+    if recipe.weight() != DEFAULT_WEIGHT {
+        text.push_str(&format!(" *{}", recipe::weight_label(recipe.weight())));
+    }
+    text
+}
+
+/// Returns the entry text if the line is a commented out recipe. A disabled recipe has its `#`
+/// directly followed by the name, e.g. `#Quiche`, whereas notes are written as `# some note` and
+/// links are recognised by their URL.
+fn disabled_entry(line: &str) -> Option<&str> {
+    let text = line.strip_prefix('#')?;
+    if text.is_empty()
+        || text.starts_with(|c: char| c.is_whitespace() || c == '#')
+        || url_comment(line).is_some()
+    {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+/// Returns the URL if the line is a comment consisting of only a link
+fn url_comment(line: &str) -> Option<&str> {
+    let url = line.strip_prefix('#')?.trim();
+    if (url.starts_with("https://") || url.starts_with("http://")) && !url.contains(' ') {
+        Some(url)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "# Dinners\n\
+        Lasagne\n\
+        # https://example.com/lasagne\n\
+        \n\
+        #Quiche\n\
+        # ## notes\n\
+        Pasta pesto [vegetarian] *favourite\n";
+
+    /// Writes the document to a fresh file and reads it back as text
+    fn saved(mut document: InputDocument, name: &str) -> String {
+        let path = std::env::temp_dir().join(format!(
+            "dinner_generator_document_{}_{}.txt",
+            name,
+            std::process::id()
+        ));
+        document.path = path.to_string_lossy().into_owned();
+        document.save().unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_file(path).unwrap();
+        content
+    }
+
+    #[test]
+    fn reads_markers_in_any_order() {
+        let recipe = parse_entry(4, "Pasta pesto [vegetarian, quick] @20min *favourite");
+        assert_eq!(recipe.name(), "Pasta pesto");
+        assert_eq!(
+            recipe.tags(),
+            &vec!["vegetarian".to_owned(), "quick".to_owned()]
+        );
+        assert_eq!(recipe.prep_time(), Some(20));
+        assert_eq!(recipe.weight(), recipe::FAVOURITE_WEIGHT);
+
+        let reordered = parse_entry(4, "Pasta pesto *favourite @20min [vegetarian, quick]");
+        assert_eq!(reordered, recipe);
+        assert_eq!(parse_entry(4, &format_entry(&recipe)), recipe);
+    }
+
+    #[test]
+    fn reads_weights_and_times_in_all_notations() {
+        assert_eq!(
+            parse_entry(0, "Spinazie *rarely").weight(),
+            recipe::RARELY_WEIGHT
+        );
+        assert_eq!(parse_entry(0, "Sushi *2").weight(), 2.0);
+        assert_eq!(parse_entry(0, "Stoofvlees @1h").prep_time(), Some(60));
+        assert_eq!(parse_entry(0, "Wrap @15m").prep_time(), Some(15));
+    }
+
+    #[test]
+    fn keeps_text_which_is_no_marker_in_the_name() {
+        for name in [
+            "Pizza *",
+            "Sushi *lots",
+            "Soup @home",
+            "[vegetarian]",
+            "Pasta [a] b",
+        ] {
+            let recipe = parse_entry(0, name);
+            assert_eq!(recipe.name(), name);
+            assert_eq!(recipe.weight(), DEFAULT_WEIGHT);
+            assert!(recipe.tags().is_empty());
+            assert_eq!(recipe.prep_time(), None);
+        }
+    }
+
+    #[test]
+    fn saving_keeps_the_file_unchanged() {
+        let document = InputDocument::parse("input.txt", INPUT);
+        assert_eq!(saved(document, "unchanged"), INPUT);
+    }
+
+    #[test]
+    fn reads_entries_disabled_entries_and_links() {
+        let document = InputDocument::parse("input.txt", INPUT);
+        let recipes = document.recipes();
+        let names: Vec<&str> = recipes.iter().map(|r| r.name()).collect();
+        assert_eq!(names, ["Lasagne", "Pasta pesto"]);
+        assert_eq!(recipes[0].source(), Some("https://example.com/lasagne"));
+        assert_eq!(recipes[1].id(), 2);
+
+        let disabled = document.disabled_recipes();
+        assert_eq!(disabled.len(), 1);
+        assert_eq!((disabled[0].id(), disabled[0].name()), (1, "Quiche"));
+        assert_eq!(document.next_id(), 3);
+    }
+
+    #[test]
+    fn edits_only_touch_their_own_line() {
+        let mut document = InputDocument::parse("input.txt", INPUT);
+        let mut pesto = document.recipes().remove(1);
+        pesto.set_weight(DEFAULT_WEIGHT);
+        assert!(document.update(&pesto));
+        assert!(document.disable(0));
+        assert!(document.enable(1));
+        let mut soup = Recipe::new(document.next_id(), "Soup".to_owned());
+        soup.set_source(Some("https://example.com/soup".to_owned()));
+        document.add(&soup);
+        assert!(!document.disable(7));
+
+        assert_eq!(
+            saved(document, "edited"),
+            "# Dinners\n\
+            #Lasagne\n\
+            # https://example.com/lasagne\n\
+            \n\
+            Quiche\n\
+            # ## notes\n\
+            Pasta pesto [vegetarian]\n\
+            Soup\n\
+            #https://example.com/soup\n"
+        );
+    }
+}
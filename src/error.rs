@@ -0,0 +1,84 @@
+use std::fmt;
+use std::io;
+
+/// Errors returned by the planner and its storage
+#[derive(Debug)]
+pub enum Error {
+    /// No entry with the given name
+    NotFound(String),
+    /// Nothing matches a selector, with similar entries the user may have meant
+    NoMatch {
+        query: String,
+        suggestions: Vec<String>,
+    },
+    /// Several entries match a selector, or a single one only resembles it, with their indices
+    /// and how to show them
+    Ambiguous {
+        query: String,
+        candidates: Vec<(usize, String)>,
+    },
+    /// Index outside of the days, pool or archive
+    InvalidIndex(usize),
+    /// No recipes left in the pool to pick from
+    EmptyPool,
+    /// Reading or writing a file failed
+    Io { path: String, source: io::Error },
+    /// A value could not be read, e.g. a weight or a slot kind
+    Parse(String),
+    /// The config file could not be read or written
+    Config(String),
+    /// A command was called without the values it needs
+    Usage(String),
+}
+
+/// Result with the crate's [`Error`]
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Wraps an io error together with the path it occurred on, for use with `map_err`
+    pub fn io(path: &str) -> impl FnOnce(io::Error) -> Error + '_ {
+        move |source| Error::Io {
+            path: path.to_owned(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound(entry) => write!(f, "Entry {} does not exist", entry),
+            Error::NoMatch { query, suggestions } if suggestions.is_empty() => {
+                write!(f, "Nothing matches {}", query)
+            }
+            Error::NoMatch { query, suggestions } => write!(
+                f,
+                "Nothing matches {}, did you mean: {}?",
+                query,
+                suggestions.join(", ")
+            ),
+            Error::Ambiguous { query, candidates } if candidates.len() == 1 => {
+                write!(f, "{} does not exactly match {}", query, candidates[0].1)
+            }
+            Error::Ambiguous { query, candidates } => {
+                let names: Vec<&str> = candidates.iter().map(|(_, name)| name.as_str()).collect();
+                write!(f, "{} matches several entries: {}", query, names.join(", "))
+            }
+            Error::InvalidIndex(index) => write!(f, "Invalid index {}", index),
+            Error::EmptyPool => write!(f, "No recipes left in the pool to pick from"),
+            Error::Io { path, source } => write!(f, "{}: {}", path, source),
+            Error::Parse(message) | Error::Config(message) | Error::Usage(message) => {
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -1,14 +1,15 @@
 use std::fs::{File, OpenOptions};
-use std::io::{self, BufRead, BufReader, Write};
-use std::path::Path;
+use std::io::{BufRead, BufReader, Write};
 
-pub fn read_file(file_name: &str) -> Result<Vec<String>, io::Error> {
-    let file = File::open(file_name)?;
+use crate::error::{Error, Result};
+
+pub fn read_file(file_name: &str) -> Result<Vec<String>> {
+    let file = File::open(file_name).map_err(Error::io(file_name))?;
     let reader = BufReader::new(file);
     let mut input = Vec::new();
 
     for line in reader.lines() {
-        let line = line?;
+        let line = line.map_err(Error::io(file_name))?;
         if !line.starts_with('#') {
             input.push(line);
         }
@@ -17,42 +18,16 @@ pub fn read_file(file_name: &str) -> Result<Vec<String>, io::Error> {
     Ok(input)
 }
 
-pub fn write_file(data: &[String], file_name: &str) -> Result<(), io::Error> {
+pub fn write_file(data: &[String], file_name: &str) -> Result<()> {
     let mut file = OpenOptions::new()
         .write(true)
         .create(true)
         .truncate(true)
-        .open(file_name)?;
+        .open(file_name)
+        .map_err(Error::io(file_name))?;
 
     for line in data {
-        writeln!(file, "{}", line)?;
-    }
-
-    Ok(())
-}
-pub fn comment_out_in_file(file_name: &str, line_to_comment: &str) -> Result<(), io::Error> {
-    let file_path = Path::new(file_name);
-    let mut lines = vec![];
-
-    // Read all lines
-    if let Ok(file) = File::open(file_path) {
-        for line in BufReader::new(file).lines() {
-            let line = line?;
-            if line == line_to_comment {
-                lines.push(format!("#{}", line));
-            } else {
-                lines.push(line);
-            }
-        }
-    }
-
-    // Write the modified lines back to the file
-    let mut file = OpenOptions::new()
-        .write(true)
-        .truncate(true)
-        .open(file_name)?;
-    for line in lines {
-        writeln!(file, "{}", line)?;
+        writeln!(file, "{}", line).map_err(Error::io(file_name))?;
     }
 
     Ok(())
@@ -1,184 +1,898 @@
-use rand::prelude::SliceRandom;
-use std::{
-    fs,
-    io::{self},
-};
+use chrono::{Datelike, Duration, Local, NaiveDate, Weekday};
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+use rand::distributions::WeightedIndex;
+use rand::prelude::{Distribution, SliceRandom};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::{collections::BTreeSet, fs};
 
+use crate::document::{self, InputDocument};
+use crate::error::{Error, Result};
 use crate::file_utils;
+use crate::history::{History, DATE_FORMAT};
+use crate::matching::{self, Lookup};
+use crate::qualifier;
+use crate::recipe::Recipe;
+use crate::settings::Settings;
+use crate::slot::{Slot, SlotKind};
 use rand::Rng;
 
+const SEED_PREFIX: &str = "# seed: ";
+const LOCKED_PREFIX: &str = "# locked: ";
+const START_PREFIX: &str = "# start: ";
+/// Amount of days after which the recency score of a recipe stops growing, also used for
+/// recipes which were never planned
+const RECENCY_CAP_DAYS: i64 = 180;
+/// Amount of orders tried when shuffling while avoiding the same tag on consecutive days
+const SHUFFLE_ATTEMPTS: usize = 100;
+
+/// How recipes are picked from the pool
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum Strategy {
+    /// Random pick according to the recipe weights
+    #[default]
+    Random,
+    /// Like random, but recipes which were not planned for a long time are more likely
+    Recency,
+}
+
+/// Dinner plan for the coming days together with the pool of recipes it picks from. Changes
+/// to the pool are written back to the input file it was read from, if any.
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
 pub struct Generate {
-    days: Vec<String>,
-    pool: Vec<String>,
+    /// Planned days, the first one falling on `start`
+    days: Vec<Slot>,
+    start: NaiveDate,
+    pool: Vec<Recipe>,
+    /// Recipes which are disabled in the input, kept so they can be restored
+    archive: Vec<Recipe>,
+    input: Option<InputDocument>,
+    history: History,
+    strategy: Strategy,
+    /// Seed of the last random change to the days: a generation, regeneration of a day, fill or
+    /// shuffle. Each of these replaces it, so it only recreates the whole plan until the next one
+    seed: u64,
+    /// Seed to use for the next generation instead of a random one
+    next_seed: Option<u64>,
+    rng: StdRng,
+    /// Indices of days which are kept when regenerating or shuffling all days
+    locked: BTreeSet<usize>,
+    /// Recipes which were picked despite their cooldown during the last selection
+    relaxed: Vec<String>,
+    settings: Settings,
+    /// Only recipes with one of these tags are picked, if any are given
+    include_tags: Vec<String>,
+    /// Recipes with one of these tags are never picked
+    exclude_tags: Vec<String>,
 }
 
+/// Collection of recipes to look an entry up in
 #[derive(Eq, PartialEq)]
 pub enum Collection {
+    /// The planned days
     Days,
+    /// The recipes which can be picked
     Pool,
+    /// The disabled recipes
+    Archive,
 }
 
-#[allow(dead_code)]
 impl Generate {
-    fn new(days: Vec<String>, pool: Vec<String>) -> Self {
-        Self { days, pool }
+    fn new(days: Vec<Slot>, pool: Vec<Recipe>) -> Self {
+        Self {
+            days,
+            start: Self::today(),
+            pool,
+            archive: vec![],
+            input: None,
+            history: History::default(),
+            strategy: Strategy::default(),
+            seed: 0,
+            next_seed: None,
+            rng: StdRng::from_entropy(),
+            locked: BTreeSet::new(),
+            relaxed: vec![],
+            settings: Settings::default(),
+            include_tags: vec![],
+            exclude_tags: vec![],
+        }
     }
 
-    pub fn days(&self) -> &Vec<String> {
+    /// Planner over the given recipes without any files, nothing is read or written until
+    /// `write_file` is called
+    pub fn from_recipes(recipes: Vec<Recipe>, settings: Settings) -> Self {
+        Self {
+            settings,
+            ..Self::new(vec![], recipes)
+        }
+    }
+
+    pub fn days(&self) -> &Vec<Slot> {
         &self.days
     }
 
-    pub fn pool(&self) -> &Vec<String> {
+    pub fn start(&self) -> NaiveDate {
+        self.start
+    }
+
+    /// Date of the day at the given index
+    pub fn date_of(&self, index: usize) -> NaiveDate {
+        self.start + Duration::days(index as i64)
+    }
+
+    pub fn pool(&self) -> &Vec<Recipe> {
         &self.pool
     }
 
+    pub fn archive(&self) -> &Vec<Recipe> {
+        &self.archive
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    pub fn cooldown_days(&self) -> i64 {
+        self.settings.cooldown_days()
+    }
+
+    pub fn set_cooldown_days(&mut self, cooldown_days: i64) {
+        self.settings.set_cooldown_days(cooldown_days);
+    }
+
+    pub fn strategy(&self) -> Strategy {
+        self.strategy
+    }
+
+    pub fn set_strategy(&mut self, strategy: Strategy) {
+        self.strategy = strategy;
+    }
+
+    /// Seed stored in the output file, see the `seed` field for what it recreates
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Makes the next generation, regeneration of a day or shuffle use the given seed, so its
+    /// result can be recreated. Picks also depend on the pool and the history, so a plan read
+    /// from an output file is only recreated after a reset: the recipes it holds are not in the
+    /// pool.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.next_seed = Some(seed);
+    }
+
+    /// Replaces the random generator the seeds are drawn from when none is set, so runs without
+    /// `set_seed` can be reproduced as well
+    pub fn set_rng(&mut self, rng: StdRng) {
+        self.rng = rng;
+    }
+
+    /// Reseeds the random generator with the seed set through `set_seed`, or a random one, and
+    /// remembers it as the seed of the current days
+    fn reseed(&mut self) {
+        self.seed = self.next_seed.take().unwrap_or_else(|| self.rng.gen());
+        self.rng = StdRng::seed_from_u64(self.seed);
+    }
+
+    pub fn relaxed(&self) -> &Vec<String> {
+        &self.relaxed
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: Settings) {
+        self.settings = settings;
+    }
+
+    pub fn include_tags(&self) -> &Vec<String> {
+        &self.include_tags
+    }
+
+    pub fn exclude_tags(&self) -> &Vec<String> {
+        &self.exclude_tags
+    }
+
+    /// Limits picking to recipes having one of the `include` tags, if any, and none of the
+    /// `exclude` tags. Unlike the cooldown this is never relaxed, days which cannot be filled
+    /// are left unplanned.
+    pub fn set_tag_filter(&mut self, include: Vec<String>, exclude: Vec<String>) {
+        self.include_tags = include;
+        self.exclude_tags = exclude;
+    }
+
+    /// Whether the tag filter allows picking the recipe
+    fn tags_allow(&self, recipe: &Recipe) -> bool {
+        (self.include_tags.is_empty() || self.include_tags.iter().any(|t| recipe.has_tag(t)))
+            && !self.exclude_tags.iter().any(|t| recipe.has_tag(t))
+    }
+
+    /// Whether the recipe may be planned on the given date, recipes without interpreted
+    /// qualifiers always may
+    fn in_season(recipe: &Recipe, date: NaiveDate) -> bool {
+        recipe
+            .qualifiers()
+            .is_none_or(|qualifiers| qualifiers.in_season(date))
+    }
+
+    /// Variant groups of the recipes planned on the given days
+    fn variant_groups(&self, days: impl Iterator<Item = usize>) -> BTreeSet<String> {
+        days.filter_map(|index| {
+            self.days
+                .get(index)?
+                .recipe()?
+                .qualifiers()?
+                .variant_group()
+        })
+        .collect()
+    }
+
+    /// Amount of days the plan should span from its start
+    pub fn plan_length(&self) -> usize {
+        self.settings.plan_length(self.start)
+    }
+
     pub fn read_entries(
         inputfile: &str,
         outputfile: &str,
-        days: usize,
+        settings: Settings,
         reset: bool,
-    ) -> Result<Self, io::Error> {
-        let pool = Self::prepare_pool(inputfile, outputfile, reset)?;
+    ) -> Result<Self> {
+        let input = InputDocument::load(inputfile)?;
+        let mut recipes = input.recipes();
+        let mut archive = input.disabled_recipes();
+        if settings.qualifiers() {
+            qualifier::apply(recipes.iter_mut().chain(archive.iter_mut()));
+        }
+        let pool = Self::prepare_pool(&recipes, outputfile, reset)?;
         if reset {
             Self::reset_output_file(outputfile)?;
         }
-        if let Ok(entries) = file_utils::read_file(outputfile) {
-            Ok(Self {
-                days: entries,
-                pool,
-            })
-        } else {
-            Ok(Self::new(vec![], pool).generate_days(days, false))
+        let mut generate = Self {
+            pool,
+            archive,
+            input: Some(input),
+            history: History::load(outputfile)?,
+            seed: Self::read_header(outputfile, SEED_PREFIX)
+                .and_then(|seed| seed.parse().ok())
+                .unwrap_or_default(),
+            locked: Self::read_header(outputfile, LOCKED_PREFIX)
+                .map(|locked| {
+                    locked
+                        .split_whitespace()
+                        .filter_map(|index| index.parse().ok())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            settings,
+            ..Self::new(vec![], vec![])
+        };
+        if let Some((start, entries)) = Self::read_output(outputfile) {
+            generate.start = start;
+            for line in entries {
+                let slot = match Slot::from_marker(&line) {
+                    Some(slot) => slot,
+                    None => match recipes.iter().find(|r| r.name() == line) {
+                        Some(recipe) => Slot::Planned(recipe.clone()),
+                        None => Slot::Planned(Recipe::new(generate.next_id(), line)),
+                    },
+                };
+                generate.days.push(slot);
+            }
         }
+        Ok(generate)
     }
 
-    pub fn generate_days(&mut self, days: usize, retain: bool) -> Self {
-        let days = if days > self.pool.len() {
-            self.pool.len()
-        } else {
-            days
-        };
+    /// Picks new recipes for all days of the plan, keeping locked days and days which are
+    /// skipped, eaten out or reserved for leftovers as they are. Days on weekdays which are not
+    /// planned are skipped and days which cannot be filled are left unplanned. With `retain` the
+    /// recipes of the replaced days go back into the pool afterwards.
+    pub fn generate_days(&mut self, retain: bool) -> Self {
+        let days = self.plan_length();
+        let kept: BTreeSet<usize> = (0..days.min(self.days.len()))
+            .filter(|&i| self.is_locked(i) || self.days[i].is_reserved())
+            .collect();
+        let skipped: BTreeSet<usize> = (0..days)
+            .filter(|&i| !kept.contains(&i) && !self.settings.plans(self.date_of(i)))
+            .collect();
+        let dates: Vec<NaiveDate> = (0..days)
+            .filter(|i| !kept.contains(i) && !skipped.contains(i))
+            .take(self.pool.len())
+            .map(|i| self.date_of(i))
+            .collect();
+
+        let used = self.variant_groups(kept.iter().copied());
+        let mut selected_entries = self.select_random_entries(&dates, used).into_iter();
 
-        let selected_entries = &Self::select_random_entries(&mut self.pool, days);
+        let mut entries = Vec::with_capacity(days);
+        for index in 0..days {
+            if kept.contains(&index) {
+                entries.push(self.days[index].clone());
+            } else if skipped.contains(&index) {
+                entries.push(Slot::Skip);
+            } else if let Some(entry) = selected_entries.next().flatten() {
+                entries.push(Slot::Planned(entry));
+            } else {
+                entries.push(Slot::Unplanned);
+            }
+        }
 
+        let replaced = std::mem::replace(&mut self.days, entries);
+        self.locked.retain(|&i| i < days);
         if retain {
-            for i in &self.days {
-                self.pool.push(i.to_owned());
+            for (index, slot) in replaced.into_iter().enumerate() {
+                if let (false, Slot::Planned(recipe)) = (kept.contains(&index), slot) {
+                    self.return_to_pool(recipe);
+                }
             }
         }
+        self.clone()
+    }
 
-        Self {
-            days: selected_entries.to_vec(),
-            pool: self.pool.clone(),
+    /// Picks recipes for the unplanned days only, first adding days until the plan covers the
+    /// horizon. New days on weekdays which are not planned are skipped.
+    pub fn fill_unplanned(&mut self) {
+        let days = self.plan_length();
+        for index in self.days.len()..days {
+            self.days.push(if self.settings.plans(self.date_of(index)) {
+                Slot::Unplanned
+            } else {
+                Slot::Skip
+            });
+        }
+        let empty: Vec<usize> = (0..self.days.len())
+            .filter(|&i| self.days[i].is_unplanned() && !self.is_locked(i))
+            .collect();
+        let dates: Vec<NaiveDate> = empty
+            .iter()
+            .take(self.pool.len())
+            .map(|&i| self.date_of(i))
+            .collect();
+        if dates.is_empty() {
+            return;
+        }
+        let used = self.variant_groups(0..self.days.len());
+        let selected_entries = self.select_random_entries(&dates, used);
+        for (index, entry) in empty.into_iter().zip(selected_entries) {
+            if let Some(entry) = entry {
+                self.days[index] = Slot::Planned(entry);
+            }
         }
     }
 
-    pub fn remove_from_days(&mut self, index: usize) {
-        self.days.remove(index);
+    /// Clears a day, putting its recipe back into the pool. The day itself stays so the
+    /// following days keep their date.
+    pub fn remove_from_days(&mut self, index: usize) -> Result<()> {
+        self.set_day_slot(index, SlotKind::Unplanned)
     }
 
-    fn prepare_pool(
-        inputfile: &str,
-        outputfile: &str,
-        reset: bool,
-    ) -> Result<Vec<String>, io::Error> {
+    /// Drops the first `count` days and moves the others forward
+    pub fn advance_days(&mut self, count: usize) {
+        let len = self.days.len();
+        self.days.drain(..count.min(len));
+        self.start += Duration::days(count as i64);
+        self.locked = self
+            .locked
+            .iter()
+            .filter(|&&i| i >= count)
+            .map(|&i| i - count)
+            .collect();
+    }
+
+    /// Moves all days before today into the history and puts their recipes back into the pool,
+    /// so the plan starts today again however long ago it was last opened. The plan is not
+    /// topped up, callers which save it do so with `fill_unplanned`. Returns the amount of days
+    /// dropped.
+    pub fn rotate(&mut self) -> usize {
+        let past = (Self::today() - self.start).num_days();
+        let Ok(past) = usize::try_from(past) else {
+            return 0;
+        };
+        if past == 0 {
+            return 0;
+        }
+        let mut rotated = vec![];
+        for index in 0..past.min(self.days.len()) {
+            if let Some(recipe) = self.days[index].recipe() {
+                self.history.record(self.date_of(index), recipe.name());
+                rotated.push(recipe.clone());
+            }
+        }
+        self.advance_days(past);
+        for recipe in rotated {
+            self.return_to_pool(recipe);
+        }
+        past
+    }
+
+    /// Puts a recipe which left the plan back into the pool, unless it is a one-off rather than
+    /// an enabled entry of the input, if there is one, or is in the pool or planned already
+    fn return_to_pool(&mut self, recipe: Recipe) {
+        let id = recipe.id();
+        if self.input.as_ref().is_none_or(|input| input.contains(id))
+            && !self.pool.iter().any(|r| r.id() == id)
+            && !self
+                .days
+                .iter()
+                .any(|slot| slot.recipe().is_some_and(|r| r.id() == id))
+        {
+            self.pool.push(recipe);
+        }
+    }
+
+    /// Puts a slot on a day, returning the recipe planned there before to the pool
+    fn replace_day(&mut self, index: usize, slot: Slot) {
+        if let Slot::Planned(previous) = std::mem::replace(&mut self.days[index], slot) {
+            self.return_to_pool(previous);
+        }
+    }
+
+    /// Marks a day as skipped, eaten out, leftovers or unplanned, putting a planned recipe back
+    /// into the pool
+    pub fn set_day_slot(&mut self, index: usize, kind: SlotKind) -> Result<()> {
+        if index >= self.days.len() {
+            return Err(Error::InvalidIndex(index));
+        }
+        self.replace_day(index, Slot::from(kind));
+        Ok(())
+    }
+
+    pub fn is_locked(&self, index: usize) -> bool {
+        self.locked.contains(&index)
+    }
+
+    /// Locks or unlocks a day, a locked day is kept when regenerating or shuffling all days
+    pub fn set_locked(&mut self, index: usize, locked: bool) -> Result<()> {
+        if index >= self.days.len() {
+            return Err(Error::InvalidIndex(index));
+        }
+        if locked {
+            self.locked.insert(index);
+        } else {
+            self.locked.remove(&index);
+        }
+        Ok(())
+    }
+
+    fn prepare_pool(recipes: &[Recipe], outputfile: &str, reset: bool) -> Result<Vec<Recipe>> {
         if !std::path::Path::new(outputfile).exists() || reset {
-            println!("outputfile no exists");
-            file_utils::read_file(inputfile)
+            Ok(recipes.to_vec())
         } else {
-            println!("{} outputfile exists", outputfile);
-            let initial = file_utils::read_file(inputfile)?;
-            let prev = file_utils::read_file(outputfile)?;
-            Ok(Self::subtract_slices(initial, prev))
+            let prev = Self::read_output(outputfile)
+                .map(|(_, entries)| entries)
+                .unwrap_or_default();
+            Ok(Self::subtract_slices(recipes.to_vec(), prev))
         }
     }
 
-    fn reset_output_file(outputfile: &str) -> Result<(), io::Error> {
+    /// Id for a new recipe. The input document decides, as it also holds the lines of recipes
+    /// which are not in memory; recipes in memory are counted for planners without an input and
+    /// for one-off recipes planned on a day.
+    fn next_id(&self) -> usize {
+        self.days
+            .iter()
+            .filter_map(|slot| slot.recipe())
+            .chain(self.pool.iter())
+            .chain(self.archive.iter())
+            .map(|r| r.id() + 1)
+            .chain(self.input.as_ref().map(InputDocument::next_id))
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn reset_output_file(outputfile: &str) -> Result<()> {
         if std::path::Path::new(outputfile).exists() {
-            println!("Resetting output");
-            let _ = fs::remove_file::<_>(outputfile);
+            fs::remove_file(outputfile).map_err(Error::io(outputfile))?;
         }
         Ok(())
     }
 
-    // 4. Selects random entries from the pool
-    fn select_random_entries(pool: &mut Vec<String>, days: usize) -> Vec<String> {
-        let mut selected_entries = Vec::with_capacity(days);
-        let mut rng = rand::thread_rng();
+    /// Reads a value stored in the output file, these are kept in comments so older versions
+    /// still read the file
+    fn read_header(outputfile: &str, prefix: &str) -> Option<String> {
+        fs::read_to_string(outputfile)
+            .ok()?
+            .lines()
+            .find_map(|line| Some(line.strip_prefix(prefix)?.trim().to_owned()))
+    }
+
+    /// Reads the start date and the lines of the days from the output file. Days are written as
+    /// `<date>\t<entry>`, plain lines written by older versions are dated from the start date,
+    /// or today, onwards.
+    fn read_output(outputfile: &str) -> Option<(NaiveDate, Vec<String>)> {
+        let lines = file_utils::read_file(outputfile).ok()?;
+        let mut start = Self::read_header(outputfile, START_PREFIX)
+            .and_then(|date| NaiveDate::parse_from_str(&date, DATE_FORMAT).ok());
+        let mut entries: Vec<String> = vec![];
 
-        while selected_entries.len() < days {
-            let index = rng.gen_range(0..pool.len());
-            selected_entries.push(pool.remove(index));
+        for line in lines {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let dated = line.split_once('\t').and_then(|(date, entry)| {
+                Some((NaiveDate::parse_from_str(date, DATE_FORMAT).ok()?, entry))
+            });
+            match dated {
+                Some((date, entry)) => {
+                    let offset = (date - *start.get_or_insert(date)).num_days();
+                    let Ok(offset) = usize::try_from(offset) else {
+                        continue;
+                    };
+                    if entries.len() <= offset {
+                        entries.resize(offset + 1, Slot::Unplanned.to_line());
+                    }
+                    entries[offset] = entry.to_owned();
+                }
+                None => entries.push(line),
+            }
         }
 
-        selected_entries
+        Some((start.unwrap_or_else(Self::today), entries))
     }
 
-    fn subtract_slices(slice1: Vec<String>, slice2: Vec<String>) -> Vec<String> {
-        slice1.into_iter().filter(|v| !slice2.contains(v)).collect()
+    fn today() -> NaiveDate {
+        Local::now().date_naive()
     }
 
-    pub fn write_file(&self, file_name: &str) -> Result<(), io::Error> {
-        file_utils::write_file(&self.days, file_name)
+    /// Returns the pool indices of recipes which may be picked on one of the dates, with at least
+    /// one entry per date if the pool allows it. Recipes planned within the cooldown are left
+    /// out, unless too few remain, in which case they are let back in starting with the one
+    /// planned longest ago.
+    fn eligible_entries(&mut self, dates: &[NaiveDate]) -> Vec<usize> {
+        let (eligible, relaxed) = self.cooldown_filter(dates);
+        self.relaxed = relaxed;
+        // Pick in a fixed order so a seed gives the same result regardless of the pool order
+        let mut eligible = eligible;
+        eligible.sort_by_key(|&i| self.pool[i].id());
+        eligible
     }
 
-    pub fn print_output(&self) {
-        for (idx, entry) in self.days.iter().enumerate() {
-            println!("{}: {}", idx, entry);
+    /// Splits off the recipes on cooldown as described in `eligible_entries`, returning the
+    /// eligible indices and the names of recipes let back in. Recipes left out by the tag filter
+    /// or out of season on all dates are not returned at all.
+    fn cooldown_filter(&self, dates: &[NaiveDate]) -> (Vec<usize>, Vec<String>) {
+        let today = Self::today();
+        let count = dates.len();
+        let mut eligible = Vec::with_capacity(self.pool.len());
+        let mut cooling_down = vec![];
+
+        for (index, recipe) in self.pool.iter().enumerate() {
+            if !self.tags_allow(recipe) || !dates.iter().any(|&d| Self::in_season(recipe, d)) {
+                continue;
+            }
+            match self.history.last_planned(recipe.name(), today) {
+                Some(date) if (today - date).num_days() < self.settings.cooldown_days() => {
+                    cooling_down.push((date, index))
+                }
+                _ => eligible.push(index),
+            }
         }
+
+        let mut relaxed = vec![];
+        cooling_down.sort();
+        for (_, index) in cooling_down {
+            if eligible.len() >= count {
+                break;
+            }
+            eligible.push(index);
+            relaxed.push(self.pool[index].name().to_owned());
+        }
+
+        (eligible, relaxed)
     }
 
-    pub fn regenerate_entry(&mut self, index: usize) -> Result<(), &'static str> {
-        if self.days.is_empty() || self.pool.is_empty() {
-            return Err("No entries to regenerate");
+    /// Weight of a pool recipe under the current strategy. With `Strategy::Recency` the recipe
+    /// weight is scaled by a score which grows by one for every week since it was last planned.
+    fn selection_weight(&self, index: usize, today: NaiveDate) -> f64 {
+        let recipe = &self.pool[index];
+        match self.strategy {
+            Strategy::Random => recipe.weight(),
+            Strategy::Recency => {
+                let days_since = self
+                    .history
+                    .last_planned(recipe.name(), today)
+                    .map_or(RECENCY_CAP_DAYS, |date| {
+                        (today - date).num_days().min(RECENCY_CAP_DAYS)
+                    });
+                recipe.weight() * (1.0 + days_since as f64 / 7.0)
+            }
         }
+    }
 
+    /// Picks one of the candidate pool indices according to the recipe weights, returning its
+    /// position in `candidates`. Falls back to a uniform pick if all weights are zero.
+    fn pick_weighted(&mut self, candidates: &[usize]) -> usize {
+        let today = Self::today();
+        let weights: Vec<f64> = candidates
+            .iter()
+            .map(|&i| self.selection_weight(i, today))
+            .collect();
+        match WeightedIndex::new(&weights) {
+            Ok(dist) => dist.sample(&mut self.rng),
+            Err(_) => self.rng.gen_range(0..candidates.len()),
+        }
+    }
+
+    /// Returns the chance of each pool recipe to be picked for today, recipes on cooldown
+    /// having no chance
+    pub fn odds(&self) -> Vec<(&Recipe, f64)> {
+        let today = Self::today();
+        let (eligible, _) = self.cooldown_filter(&[today]);
+        let total: f64 = eligible
+            .iter()
+            .map(|&i| self.selection_weight(i, today))
+            .sum();
+        self.pool
+            .iter()
+            .enumerate()
+            .map(|(index, recipe)| {
+                let odds = if !eligible.contains(&index) {
+                    0.0
+                } else if total > 0.0 {
+                    self.selection_weight(index, today) / total
+                } else {
+                    1.0 / eligible.len() as f64
+                };
+                (recipe, odds)
+            })
+            .collect()
+    }
+
+    /// Picks one of the candidate pool indices like `pick_weighted`, passing over variants of
+    /// the `used` groups unless only those are left, and adds the group of the pick to `used`
+    fn pick_variant(&mut self, candidates: &[usize], used: &mut BTreeSet<String>) -> usize {
+        let mut groups: Vec<Option<String>> = candidates
+            .iter()
+            .map(|&i| self.pool[i].qualifiers()?.variant_group())
+            .collect();
+        let fresh: Vec<usize> = (0..candidates.len())
+            .filter(|&i| groups[i].as_ref().is_none_or(|group| !used.contains(group)))
+            .collect();
+        let position = if fresh.is_empty() {
+            self.pick_weighted(candidates)
+        } else {
+            let choices: Vec<usize> = fresh.iter().map(|&i| candidates[i]).collect();
+            fresh[self.pick_weighted(&choices)]
+        };
+        used.extend(groups.swap_remove(position));
+        position
+    }
+
+    // 4. Selects a random entry from the pool for each date, spreading variants of the same
+    // recipe over different plans. Dates for which no recipe is in season get none.
+    fn select_random_entries(
+        &mut self,
+        dates: &[NaiveDate],
+        mut used: BTreeSet<String>,
+    ) -> Vec<Option<Recipe>> {
+        let mut candidates = self.eligible_entries(dates);
+        let mut selected = Vec::with_capacity(dates.len());
+        self.reseed();
+
+        for &date in dates {
+            let in_season: Vec<usize> = (0..candidates.len())
+                .filter(|&i| Self::in_season(&self.pool[candidates[i]], date))
+                .collect();
+            if in_season.is_empty() {
+                selected.push(None);
+                continue;
+            }
+            let choices: Vec<usize> = in_season.iter().map(|&i| candidates[i]).collect();
+            let index = in_season[self.pick_variant(&choices, &mut used)];
+            selected.push(Some(candidates.remove(index)));
+        }
+
+        let selected_entries = selected
+            .iter()
+            .map(|index| index.map(|i| self.pool[i].clone()))
+            .collect();
+        let mut selected: Vec<usize> = selected.into_iter().flatten().collect();
+        selected.sort_unstable();
+        for index in selected.into_iter().rev() {
+            self.pool.remove(index);
+        }
+
+        selected_entries
+    }
+
+    fn subtract_slices(slice1: Vec<Recipe>, slice2: Vec<String>) -> Vec<Recipe> {
+        slice1
+            .into_iter()
+            .filter(|v| !slice2.iter().any(|name| name == v.name()))
+            .collect()
+    }
+
+    /// Writes the dated days, their seed and locks to the output file and records them in the history
+    pub fn write_file(&mut self, file_name: &str) -> Result<()> {
+        let mut lines = vec![
+            format!("{}{}", SEED_PREFIX, self.seed),
+            format!("{}{}", START_PREFIX, self.start.format(DATE_FORMAT)),
+        ];
+        if !self.locked.is_empty() {
+            let locked: Vec<String> = self.locked.iter().map(|i| i.to_string()).collect();
+            lines.push(format!("{}{}", LOCKED_PREFIX, locked.join(" ")));
+        }
+        lines.extend(self.days.iter().enumerate().map(|(index, slot)| {
+            format!(
+                "{}\t{}",
+                self.date_of(index).format(DATE_FORMAT),
+                slot.to_line()
+            )
+        }));
+        file_utils::write_file(&lines, file_name)?;
+
+        self.history.forget_from(self.date_of(self.days.len()));
+        for (index, slot) in self.days.iter().enumerate() {
+            let date = self.date_of(index);
+            match slot.recipe() {
+                Some(recipe) => self.history.record(date, recipe.name()),
+                None => self.history.forget(date),
+            }
+        }
+        self.history.save()
+    }
+
+    pub fn regenerate_entry(&mut self, index: usize) -> Result<()> {
         if index >= self.days.len() {
-            return Err("Invalid index");
+            return Err(Error::InvalidIndex(index));
         }
 
-        let mut rng = rand::thread_rng();
-        let random_index = rng.gen_range(0..self.pool.len());
+        // Empty if the pool is, or if the tag filter leaves nothing
+        let candidates = self.eligible_entries(&[self.date_of(index)]);
+        if candidates.is_empty() {
+            return Err(Error::EmptyPool);
+        }
+        let mut used = self.variant_groups((0..self.days.len()).filter(|&i| i != index));
+        self.reseed();
+        let random_index = candidates[self.pick_variant(&candidates, &mut used)];
         let new_entry = self.pool.remove(random_index);
+        self.replace_day(index, Slot::Planned(new_entry));
+        Ok(())
+    }
 
-        let removed_entry = std::mem::replace(&mut self.days[index], new_entry);
-        println!("Removed entry: {:?}", removed_entry);
+    /// Finds a day by a selector: its index, `today`, `tomorrow`, a date like `2026-10-20`, a
+    /// weekday like `tue` for the first such day in the plan, or the recipe planned on it as
+    /// found by [`Generate::find_entry`].
+    pub fn find_day(&self, selector: &str) -> Result<usize> {
+        let selector = selector.trim();
+        if let Ok(index) = selector.parse::<usize>() {
+            if index >= self.days.len() {
+                return Err(Error::InvalidIndex(index));
+            }
+            return Ok(index);
+        }
+        let today = Self::today();
+        let date = match selector.to_lowercase().as_str() {
+            "today" => Some(today),
+            "tomorrow" => Some(today + Duration::days(1)),
+            _ => NaiveDate::parse_from_str(selector, DATE_FORMAT).ok(),
+        };
+        if let Some(date) = date {
+            return self
+                .index_of(date)
+                .ok_or_else(|| Error::NotFound(date.format(DATE_FORMAT).to_string()));
+        }
+        if let Ok(weekday) = selector.parse::<Weekday>() {
+            if let Some(index) =
+                (0..self.days.len()).find(|&i| self.date_of(i).weekday() == weekday)
+            {
+                return Ok(index);
+            }
+        }
+        self.find_entry(Collection::Days, selector)
+    }
 
-        self.pool.push(removed_entry);
+    /// Index of the day falling on the given date
+    pub fn index_of(&self, date: NaiveDate) -> Option<usize> {
+        usize::try_from((date - self.start).num_days())
+            .ok()
+            .filter(|&index| index < self.days.len())
+    }
 
-        Ok(())
+    /// Finds an entry by name in the given collection, ignoring case, accents and punctuation
+    /// and allowing for typos. Fails with the candidates if several entries match, or with
+    /// similar entries if none does.
+    pub fn find_entry(&self, col: Collection, entry: &str) -> Result<usize> {
+        self.lookup(col, entry, false)
     }
 
-    pub fn find_entry(&self, col: Collection, entry: &str) -> Option<usize> {
-        let entries = if col == Collection::Pool {
-            &self.pool
-        } else {
-            &self.days
+    /// Like `find_entry`, but a single match which is not the same name after normalizing, e.g.
+    /// `Soep` for `Soep met broodjes`, fails as ambiguous too so it can be confirmed first
+    pub fn find_exact_entry(&self, col: Collection, entry: &str) -> Result<usize> {
+        self.lookup(col, entry, true)
+    }
+
+    fn lookup(&self, col: Collection, entry: &str, exact: bool) -> Result<usize> {
+        let names: Vec<&str> = match col {
+            Collection::Days => self
+                .days
+                .iter()
+                .map(|slot| slot.recipe().map_or("", |r| r.name()))
+                .collect(),
+            Collection::Pool => self.pool.iter().map(|r| r.name()).collect(),
+            Collection::Archive => self.archive.iter().map(|r| r.name()).collect(),
         };
-        entries.iter().position(|x| x == entry)
+        let label = |index: usize| match col {
+            Collection::Days => {
+                let date = self.date_of(index).format("%a %-d %b");
+                format!("{} ({})", self.days[index], date)
+            }
+            _ => names[index].to_owned(),
+        };
+        match matching::find(entry, names.iter().copied()) {
+            Lookup::Found(index) if !exact || matching::same(names[index], entry) => Ok(index),
+            Lookup::Found(index) => Err(Error::Ambiguous {
+                query: entry.to_owned(),
+                candidates: vec![(index, label(index))],
+            }),
+            Lookup::Ambiguous(indices) => Err(Error::Ambiguous {
+                query: entry.to_owned(),
+                candidates: indices.into_iter().map(|i| (i, label(i))).collect(),
+            }),
+            Lookup::Missing(indices) => Err(Error::NoMatch {
+                query: entry.to_owned(),
+                suggestions: indices.into_iter().map(label).collect(),
+            }),
+        }
+    }
+
+    /// Applies a change to the input document, if any, and writes it back to disk
+    fn persist<F>(&mut self, change: F) -> Result<()>
+    where
+        F: FnOnce(&mut InputDocument),
+    {
+        if let Some(input) = &mut self.input {
+            change(input);
+            input.save()?;
+        }
+        Ok(())
     }
 
-    pub fn add_to_pool(&mut self, new_entry: String) {
-        self.pool.push(new_entry);
+    /// Adds a recipe, reading tags, preparation time and weight from markers after its name
+    /// like in the input file
+    pub fn add_to_pool(&mut self, new_entry: String) -> Result<()> {
+        let recipe = document::parse_entry(self.next_id(), &new_entry);
+        self.persist(|input| input.add(&recipe))?;
+        self.pool.push(recipe);
+        self.apply_qualifiers();
+        Ok(())
     }
 
-    pub fn remove_from_pool_by_strvalue(
-        &mut self,
-        entry: &str,
-        input_file: &str,
-    ) -> Result<(), io::Error> {
-        if let Some(pos) = self.find_entry(Collection::Pool, entry) {
-            self.pool.remove(pos);
-            file_utils::comment_out_in_file(input_file, entry)?;
+    pub fn remove_from_pool_by_strvalue(&mut self, entry: &str) -> Result<()> {
+        let pos = self.find_exact_entry(Collection::Pool, entry)?;
+        self.remove_from_pool(pos)
+    }
+
+    pub fn remove_from_pool(&mut self, index: usize) -> Result<()> {
+        if index >= self.pool.len() {
+            return Err(Error::InvalidIndex(index));
         }
+        let removed = self.pool.remove(index);
+        self.persist(|input| {
+            input.disable(removed.id());
+        })?;
+        self.archive.push(removed);
         Ok(())
     }
 
-    pub fn remove_from_pool(&mut self, index: usize, input_file: &str) -> Result<(), io::Error> {
-        self.pool.remove(index);
-        file_utils::comment_out_in_file(input_file, &self.pool[index])?;
+    pub fn restore_from_archive_by_strvalue(&mut self, entry: &str) -> Result<()> {
+        let pos = self.find_entry(Collection::Archive, entry)?;
+        self.restore_from_archive(pos)
+    }
+
+    /// Re-enables a disabled recipe, moving it back into the pool
+    pub fn restore_from_archive(&mut self, index: usize) -> Result<()> {
+        if index >= self.archive.len() {
+            return Err(Error::InvalidIndex(index));
+        }
+        let restored = self.archive.remove(index);
+        self.persist(|input| {
+            input.enable(restored.id());
+        })?;
+        self.pool.push(restored);
         Ok(())
     }
 
@@ -186,41 +900,687 @@ impl Generate {
         &mut self,
         old_entry: &str,
         new_entry: String,
-    ) -> Result<(), &'static str> {
-        if let Some(pos) = self.find_entry(Collection::Pool, old_entry) {
-            self.pool[pos] = new_entry;
-            Ok(())
-        } else {
-            Err("Entry not found in pool")
+    ) -> Result<()> {
+        let pos = self.find_exact_entry(Collection::Pool, old_entry)?;
+        self.edit_pool_entry(pos, new_entry)
+    }
+
+    pub fn edit_pool_entry(&mut self, index: usize, new_entry: String) -> Result<()> {
+        if index >= self.pool.len() {
+            return Err(Error::InvalidIndex(index));
+        }
+        let mut recipe = self.pool[index].clone();
+        recipe.set_name(new_entry);
+        self.update_pool_entry(index, recipe)
+    }
+
+    pub fn set_pool_weight_by_strvalue(&mut self, entry: &str, weight: f64) -> Result<()> {
+        let pos = self.find_exact_entry(Collection::Pool, entry)?;
+        self.set_pool_weight(pos, weight)
+    }
+
+    pub fn set_pool_weight(&mut self, index: usize, weight: f64) -> Result<()> {
+        if index >= self.pool.len() {
+            return Err(Error::InvalidIndex(index));
         }
+        let mut recipe = self.pool[index].clone();
+        recipe.set_weight(weight);
+        self.update_pool_entry(index, recipe)
     }
 
-    pub fn edit_pool_entry(&mut self, index: usize, new_entry: String) -> Result<(), &'static str> {
+    pub fn set_pool_tags(&mut self, index: usize, tags: Vec<String>) -> Result<()> {
         if index >= self.pool.len() {
-            return Err("Invalid index");
+            return Err(Error::InvalidIndex(index));
         }
-        self.pool[index] = new_entry;
+        let mut recipe = self.pool[index].clone();
+        recipe.set_tags(tags);
+        self.update_pool_entry(index, recipe)
+    }
+
+    /// Replaces a pool recipe and writes the change to the input
+    fn update_pool_entry(&mut self, index: usize, recipe: Recipe) -> Result<()> {
+        self.persist(|input| {
+            input.update(&recipe);
+        })?;
+        self.pool[index] = recipe;
+        self.apply_qualifiers();
         Ok(())
     }
 
-    pub fn edit_days_entry(&mut self, index: usize, new_entry: String) -> Result<(), &'static str> {
+    /// Interprets the parentheses in the names again after a recipe was added or renamed, if
+    /// the qualifiers setting is on, so its seasons and the variant groups stay current
+    fn apply_qualifiers(&mut self) {
+        if !self.settings.qualifiers() {
+            return;
+        }
+        let planned = self.days.iter_mut().filter_map(|slot| match slot {
+            Slot::Planned(recipe) => Some(recipe),
+            _ => None,
+        });
+        qualifier::apply(
+            self.pool
+                .iter_mut()
+                .chain(self.archive.iter_mut())
+                .chain(planned),
+        );
+    }
+
+    /// Plans a value on a day: the pool recipe of the same name, or a new recipe if it resembles
+    /// none. Fails with the candidates if it only resembles pool recipes, one of which can then
+    /// be planned with `plan_pool_entry`, or the value as typed with `edit_days_entry`.
+    pub fn plan_entry(&mut self, index: usize, value: &str) -> Result<()> {
         if index >= self.days.len() {
-            return Err("Invalid index");
+            return Err(Error::InvalidIndex(index));
         }
-        self.days[index] = new_entry;
+        match self.find_exact_entry(Collection::Pool, value) {
+            Ok(pool_index) => self.plan_pool_entry(index, pool_index),
+            Err(Error::NoMatch { .. }) => self.edit_days_entry(index, value.to_owned()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Plans a value on a day as typed, which is the pool recipe of the same name if there is one
+    /// and a new recipe otherwise
+    pub fn edit_days_entry(&mut self, index: usize, new_entry: String) -> Result<()> {
+        if index >= self.days.len() {
+            return Err(Error::InvalidIndex(index));
+        }
+        match self
+            .pool
+            .iter()
+            .position(|r| matching::same(r.name(), &new_entry))
+        {
+            Some(pool_index) => self.plan_pool_entry(index, pool_index),
+            None => {
+                let recipe = Recipe::new(self.next_id(), new_entry);
+                self.replace_day(index, Slot::Planned(recipe));
+                Ok(())
+            }
+        }
+    }
+
+    /// Moves a recipe from the pool onto a day, returning the recipe planned there before to
+    /// the pool
+    pub fn plan_pool_entry(&mut self, index: usize, pool_index: usize) -> Result<()> {
+        if index >= self.days.len() {
+            return Err(Error::InvalidIndex(index));
+        }
+        if pool_index >= self.pool.len() {
+            return Err(Error::InvalidIndex(pool_index));
+        }
+        let recipe = self.pool.remove(pool_index);
+        self.replace_day(index, Slot::Planned(recipe));
         Ok(())
     }
 
-    pub fn swap_days_entries(&mut self, index1: usize, index2: usize) -> Result<(), &'static str> {
-        if index1 >= self.days.len() || index2 >= self.days.len() {
-            return Err("Invalid index");
+    pub fn swap_days_entries(&mut self, index1: usize, index2: usize) -> Result<()> {
+        for index in [index1, index2] {
+            if index >= self.days.len() {
+                return Err(Error::InvalidIndex(index));
+            }
         }
         self.days.swap(index1, index2);
-        Ok(())
+        // A lock belongs to the recipe, e.g. because its groceries are bought, so it moves along
+        let (locked1, locked2) = (self.is_locked(index1), self.is_locked(index2));
+        self.set_locked(index1, locked2)?;
+        self.set_locked(index2, locked1)
+    }
+
+    /// Shuffles the order of the days, leaving locked days and days which are skipped, eaten out
+    /// or reserved for leftovers in place, as well as today with `keep_today`. With
+    /// `spread_tags` orders with recipes sharing a tag on consecutive days are avoided where
+    /// possible. Returns the amount of days still sharing a tag with the next.
+    pub fn randomize_days(&mut self, keep_today: bool, spread_tags: bool) -> usize {
+        self.reseed();
+        let today = Self::today();
+        let movable: Vec<usize> = (0..self.days.len())
+            .filter(|&i| !self.is_locked(i) && !self.days[i].is_reserved())
+            .filter(|&i| !keep_today || self.date_of(i) != today)
+            .collect();
+        let mut entries: Vec<Slot> = movable.iter().map(|&i| self.days[i].clone()).collect();
+        let attempts = if spread_tags { SHUFFLE_ATTEMPTS } else { 1 };
+        let mut best: Option<(usize, Vec<Slot>)> = None;
+        for _ in 0..attempts {
+            entries.shuffle(&mut self.rng);
+            let mut days = self.days.clone();
+            for (&index, entry) in movable.iter().zip(&entries) {
+                days[index] = entry.clone();
+            }
+            let clashes = Self::tag_clashes(&days);
+            if best.as_ref().is_none_or(|(least, _)| clashes < *least) {
+                best = Some((clashes, days));
+            }
+            if clashes == 0 {
+                break;
+            }
+        }
+        match best {
+            Some((clashes, days)) => {
+                self.days = days;
+                clashes
+            }
+            None => 0,
+        }
+    }
+
+    /// Amount of consecutive days whose recipes share a tag
+    fn tag_clashes(days: &[Slot]) -> usize {
+        days.windows(2)
+            .filter(|pair| match (pair[0].recipe(), pair[1].recipe()) {
+                (Some(first), Some(second)) => first.tags().iter().any(|tag| second.has_tag(tag)),
+                _ => false,
+            })
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipe::DEFAULT_WEIGHT;
+    use std::path::PathBuf;
+
+    /// Fresh directory for the files of a single test
+    fn test_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("dinner_generator_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn path(dir: &std::path::Path, file: &str) -> String {
+        dir.join(file).to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn new_recipes_do_not_reuse_ids_of_recipes_outside_the_pool() {
+        let dir = test_dir("ids");
+        let (input, output) = (path(&dir, "input.txt"), path(&dir, "output.txt"));
+        let today = Generate::today();
+        fs::write(&input, "A\nB\nC\n").unwrap();
+        fs::write(
+            &output,
+            format!(
+                "{}\tC\n{}\tA\n",
+                (today - Duration::days(1)).format(DATE_FORMAT),
+                today.format(DATE_FORMAT)
+            ),
+        )
+        .unwrap();
+
+        let mut generate = Generate::read_entries(&input, &output, Settings::default(), false)
+            .expect("files are readable");
+        generate.add_to_pool("D".to_owned()).unwrap();
+        let added = generate.find_entry(Collection::Pool, "D").unwrap();
+        generate.remove_from_pool(added).unwrap();
+
+        assert_eq!(fs::read_to_string(&input).unwrap(), "A\nB\nC\n#D\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reads_plain_lines_of_older_versions() {
+        let dir = test_dir("legacy");
+        let output = path(&dir, "output.txt");
+        fs::write(&output, "# seed: 3\nLasagne\n\nPasta pesto\n").unwrap();
+        let (start, entries) = Generate::read_output(&output).unwrap();
+        assert_eq!(start, Generate::today());
+        assert_eq!(entries, ["Lasagne", "Pasta pesto"]);
+
+        fs::write(&output, "# start: 2026-01-05\nLasagne\nPasta pesto\n").unwrap();
+        let (start, entries) = Generate::read_output(&output).unwrap();
+        assert_eq!(start, NaiveDate::from_ymd_opt(2026, 1, 5).unwrap());
+        assert_eq!(entries, ["Lasagne", "Pasta pesto"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn regenerating_drops_one_off_recipes() {
+        let dir = test_dir("one_off");
+        let (input, output) = (path(&dir, "input.txt"), path(&dir, "output.txt"));
+        fs::write(&input, "A\nB\nC\n").unwrap();
+        fs::write(&output, "Pizza\nA\n").unwrap();
+
+        let mut generate = Generate::read_entries(&input, &output, Settings::default(), false)
+            .expect("files are readable");
+        let generate = generate.generate_days(true);
+
+        let mut names: Vec<String> = generate
+            .pool()
+            .iter()
+            .map(|r| r.name().to_owned())
+            .chain(
+                generate
+                    .days()
+                    .iter()
+                    .filter_map(|slot| Some(slot.recipe()?.name().to_owned())),
+            )
+            .collect();
+        names.sort();
+        assert_eq!(names, ["A", "B", "C"]);
+        fs::remove_dir_all(dir).unwrap();
     }
 
-    pub fn randomize_days(&mut self) {
-        let mut rng = rand::thread_rng();
-        self.days.shuffle(&mut rng);
+    #[test]
+    fn migrates_plain_lines_to_dated_ones() {
+        let dir = test_dir("migrate");
+        let (input, output) = (path(&dir, "input.txt"), path(&dir, "output.txt"));
+        fs::write(&input, "A\nB\nC\n").unwrap();
+        fs::write(&output, "B\nPizza\n").unwrap();
+
+        let mut generate = Generate::read_entries(&input, &output, Settings::default(), false)
+            .expect("files are readable");
+        let ids: Vec<usize> = generate
+            .days()
+            .iter()
+            .filter_map(|slot| Some(slot.recipe()?.id()))
+            .collect();
+        assert_eq!(ids, [1, 3]);
+        let pool: Vec<&str> = generate.pool().iter().map(|r| r.name()).collect();
+        assert_eq!(pool, ["A", "C"]);
+
+        generate.write_file(&output).unwrap();
+        let today = Generate::today();
+        let content = fs::read_to_string(&output).unwrap();
+        let days: Vec<&str> = content.lines().skip(2).collect();
+        assert_eq!(
+            days,
+            [
+                format!("{}\tB", today.format(DATE_FORMAT)),
+                format!("{}\tPizza", generate.date_of(1).format(DATE_FORMAT)),
+            ]
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reads_dated_lines_leaving_gaps_unplanned() {
+        let dir = test_dir("dated");
+        let output = path(&dir, "output.txt");
+        fs::write(
+            &output,
+            "# start: 2026-01-05\n2026-01-04\tSoup\n2026-01-05\tLasagne\n2026-01-07\tPasta pesto\n",
+        )
+        .unwrap();
+        let (start, entries) = Generate::read_output(&output).unwrap();
+        assert_eq!(start, NaiveDate::from_ymd_opt(2026, 1, 5).unwrap());
+        assert_eq!(
+            entries,
+            ["Lasagne", &Slot::Unplanned.to_line(), "Pasta pesto"]
+        );
+        assert!(Generate::read_output(&path(&dir, "missing.txt")).is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    fn planner() -> Generate {
+        let recipes = ["A", "B", "C", "D", "E", "F", "G", "H", "I", "J"]
+            .into_iter()
+            .enumerate()
+            .map(|(id, name)| Recipe::new(id, name.to_owned()))
+            .collect();
+        Generate::from_recipes(recipes, Settings::default())
+    }
+
+    fn names(days: &[Slot]) -> Vec<String> {
+        days.iter().map(Slot::to_string).collect()
+    }
+
+    #[test]
+    fn cooldown_is_relaxed_oldest_first() {
+        let recipes = ["A", "B", "C", "D"]
+            .into_iter()
+            .enumerate()
+            .map(|(id, name)| Recipe::new(id, name.to_owned()))
+            .collect();
+        let mut settings = Settings::default();
+        settings.set_horizon(3);
+        let mut generate = Generate::from_recipes(recipes, settings);
+        let today = Generate::today();
+        generate.history.record(today - Duration::days(2), "A");
+        generate.history.record(today - Duration::days(5), "B");
+        generate.history.record(today - Duration::days(3), "C");
+        generate.history.record(today - Duration::days(7), "D");
+
+        let generate = generate.generate_days(false);
+
+        assert_eq!(generate.relaxed(), &["B", "C"]);
+        let mut planned = names(generate.days());
+        planned.sort();
+        assert_eq!(planned, ["B", "C", "D"]);
+        assert_eq!(generate.pool()[0].name(), "A");
+    }
+
+    fn full_week() -> Generate {
+        let recipes = [
+            "Maïskolven",
+            "Taco's",
+            "Pad thai",
+            "Pasta pesto",
+            "Pasta carbonara",
+            "Lasagne",
+            "Soep",
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(id, name)| Recipe::new(id, name.to_owned()))
+        .collect();
+        let mut generate = Generate::from_recipes(recipes, Settings::default());
+        generate.set_seed(5);
+        generate.generate_days(false)
+    }
+
+    #[test]
+    fn finds_day_by_index_and_date() {
+        let generate = full_week();
+        let date = generate.date_of(4).format(DATE_FORMAT).to_string();
+        let weekday = generate.date_of(2).format("%a").to_string().to_lowercase();
+        let before = (generate.start() - Duration::days(1))
+            .format(DATE_FORMAT)
+            .to_string();
+
+        assert_eq!(generate.find_day("3").unwrap(), 3);
+        assert!(matches!(
+            generate.find_day("7"),
+            Err(Error::InvalidIndex(7))
+        ));
+        assert_eq!(generate.find_day("today").unwrap(), 0);
+        assert_eq!(generate.find_day(" Tomorrow ").unwrap(), 1);
+        assert_eq!(generate.find_day(&date).unwrap(), 4);
+        assert!(matches!(generate.find_day(&before), Err(Error::NotFound(d)) if d == before));
+        assert_eq!(generate.find_day(&weekday).unwrap(), 2);
+    }
+
+    #[test]
+    fn finds_day_by_recipe_name() {
+        let generate = full_week();
+        let lasagne = names(generate.days())
+            .iter()
+            .position(|name| name == "Lasagne")
+            .unwrap();
+
+        assert_eq!(generate.find_day("lasagne").unwrap(), lasagne);
+        match generate.find_day("pasta pomodoro") {
+            Err(Error::NoMatch { query, suggestions }) => {
+                assert_eq!(query, "pasta pomodoro");
+                assert_eq!(suggestions.len(), 2);
+                assert!(suggestions.iter().all(|s| s.starts_with("Pasta ")));
+            }
+            other => panic!("expected suggestions, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn seed_recreates_generated_days() {
+        let mut first = planner();
+        first.set_seed(5);
+        let first = first.generate_days(false);
+        let mut second = planner();
+        second.set_seed(5);
+        let second = second.generate_days(false);
+
+        assert_eq!(first.seed(), 5);
+        assert_eq!(first.days().len(), 7);
+        assert_eq!(names(first.days()), names(second.days()));
+    }
+
+    #[test]
+    fn seed_recreates_regenerated_day() {
+        let mut first = planner();
+        first.set_seed(5);
+        let mut first = first.generate_days(false);
+        let mut second = first.clone();
+        first.set_seed(6);
+        first.regenerate_entry(2).unwrap();
+        second.set_rng(StdRng::seed_from_u64(99));
+        second.set_seed(6);
+        second.regenerate_entry(2).unwrap();
+
+        assert_eq!(first.seed(), 6);
+        assert_eq!(names(first.days()), names(second.days()));
+        assert_eq!(first.pool().len() + first.days().len(), 10);
+    }
+
+    #[test]
+    fn seed_recreates_shuffle() {
+        let mut first = planner();
+        first.set_seed(5);
+        let mut first = first.generate_days(false);
+        let mut second = first.clone();
+        first.set_seed(7);
+        first.randomize_days(false, false);
+        second.set_rng(StdRng::seed_from_u64(99));
+        second.set_seed(7);
+        second.randomize_days(false, false);
+
+        assert_eq!(names(first.days()), names(second.days()));
+    }
+
+    #[test]
+    fn injected_rng_recreates_unseeded_runs() {
+        let mut first = planner();
+        first.set_rng(StdRng::seed_from_u64(1));
+        let first = first.generate_days(false);
+        let mut second = planner();
+        second.set_rng(StdRng::seed_from_u64(1));
+        let second = second.generate_days(false);
+
+        assert_eq!(first.seed(), second.seed());
+        assert_eq!(names(first.days()), names(second.days()));
+    }
+
+    #[test]
+    fn planning_a_pool_recipe_moves_it_and_returns_the_previous_one() {
+        let mut generate = planner();
+        generate.set_seed(5);
+        let mut generate = generate.generate_days(false);
+        let previous = generate.days()[0].to_string();
+        let pick = generate.pool()[0].name().to_owned();
+
+        generate.plan_entry(0, &pick.to_lowercase()).unwrap();
+
+        assert_eq!(generate.days()[0].to_string(), pick);
+        let pool: Vec<&str> = generate.pool().iter().map(|r| r.name()).collect();
+        assert!(!pool.contains(&pick.as_str()));
+        assert!(pool.contains(&previous.as_str()));
+        assert_eq!(generate.pool().len(), 3);
+    }
+
+    #[test]
+    fn planning_a_value_resembling_a_pool_recipe_asks_first() {
+        let recipes = vec![Recipe::new(0, "Soep met broodjes".to_owned())];
+        let mut generate = Generate::from_recipes(recipes, Settings::default());
+        generate.fill_unplanned();
+        generate.set_day_slot(0, SlotKind::Unplanned).unwrap();
+
+        match generate.plan_entry(0, "Soep") {
+            Err(Error::Ambiguous { candidates, .. }) => assert_eq!(candidates[0].0, 0),
+            other => panic!("expected a question, got {:?}", other),
+        }
+        assert!(generate.days()[0].is_unplanned());
+        generate.edit_days_entry(0, "Soep".to_owned()).unwrap();
+        assert_eq!(generate.days()[0].to_string(), "Soep");
+        assert_eq!(generate.pool().len(), 1);
+    }
+
+    #[test]
+    fn locks_move_along_when_swapping() {
+        let mut generate = planner();
+        generate.set_seed(5);
+        let mut generate = generate.generate_days(false);
+        let locked = generate.days()[1].to_string();
+        generate.set_locked(1, true).unwrap();
+
+        generate.swap_days_entries(1, 4).unwrap();
+
+        assert_eq!(generate.days()[4].to_string(), locked);
+        assert!(generate.is_locked(4));
+        assert!(!generate.is_locked(1));
+    }
+
+    #[test]
+    fn locked_and_reserved_days_are_kept() {
+        let mut generate = planner();
+        generate.set_seed(5);
+        let mut generate = generate.generate_days(false);
+        let locked = generate.days()[2].to_string();
+        generate.set_locked(2, true).unwrap();
+        generate.set_day_slot(5, SlotKind::Leftovers).unwrap();
+        assert!(generate.set_locked(7, true).is_err());
+
+        for seed in 0..20 {
+            generate.set_seed(seed);
+            generate = generate.generate_days(true);
+            assert_eq!(generate.days()[2].to_string(), locked);
+            assert_eq!(generate.days()[5], Slot::Leftovers);
+            generate.set_seed(seed);
+            generate.randomize_days(false, false);
+            assert_eq!(generate.days()[2].to_string(), locked);
+            assert_eq!(generate.days()[5], Slot::Leftovers);
+            assert!(generate.is_locked(2));
+        }
+        assert_eq!(generate.pool().len() + generate.days().len(), 11);
+    }
+
+    #[test]
+    fn shuffle_keeps_today_and_spreads_tags() {
+        let recipes = ["A", "B", "C", "D"]
+            .into_iter()
+            .enumerate()
+            .map(|(id, name)| {
+                let mut recipe = Recipe::new(id, name.to_owned());
+                let tag = if id % 2 == 0 { "fish" } else { "meat" };
+                recipe.set_tags(vec![tag.to_owned()]);
+                recipe
+            })
+            .collect();
+        let mut settings = Settings::default();
+        settings.set_horizon(4);
+        let mut generate = Generate::from_recipes(recipes, settings);
+        generate.set_seed(5);
+        let mut generate = generate.generate_days(false);
+        let today = generate.days()[0].to_string();
+
+        for seed in 0..20 {
+            generate.set_seed(seed);
+            assert_eq!(generate.randomize_days(true, true), 0);
+            assert_eq!(generate.days()[0].to_string(), today);
+            assert_eq!(Generate::tag_clashes(generate.days()), 0);
+        }
+    }
+
+    #[test]
+    fn added_and_renamed_recipes_are_interpreted() {
+        let mut settings = Settings::default();
+        settings.set_qualifiers(true);
+        let recipes = vec![Recipe::new(0, "Rijst (zoetzuur)".to_owned())];
+        let mut generate = Generate::from_recipes(recipes, settings);
+        let seasons = |generate: &Generate, index: usize| -> Vec<qualifier::Season> {
+            let qualifiers = generate.pool()[index].qualifiers().unwrap();
+            qualifiers.seasons().clone()
+        };
+
+        generate.add_to_pool("Salade (zomer)".to_owned()).unwrap();
+        assert_eq!(seasons(&generate, 1), [qualifier::Season::Summer]);
+        generate
+            .edit_pool_entry(1, "Salade (winter)".to_owned())
+            .unwrap();
+        assert_eq!(seasons(&generate, 1), [qualifier::Season::Winter]);
+
+        generate
+            .add_to_pool("Rijst (thai curry)".to_owned())
+            .unwrap();
+        let rice = generate.pool()[0].qualifiers().unwrap();
+        assert_eq!(rice.variant_group(), Some("rijst".to_owned()));
+    }
+
+    #[test]
+    fn changing_the_input_needs_an_exact_name() {
+        let recipes = vec![Recipe::new(0, "Stamppot(winter)".to_owned())];
+        let mut generate = Generate::from_recipes(recipes, Settings::default());
+
+        assert!(matches!(
+            generate.remove_from_pool_by_strvalue("Stamppot"),
+            Err(Error::Ambiguous { .. })
+        ));
+        assert!(matches!(
+            generate.set_pool_weight_by_strvalue("stampot(winter)", 2.0),
+            Err(Error::Ambiguous { .. })
+        ));
+        assert_eq!(generate.pool().len(), 1);
+        assert_eq!(generate.pool()[0].weight(), DEFAULT_WEIGHT);
+
+        generate
+            .remove_from_pool_by_strvalue("stamppot (Winter)")
+            .unwrap();
+        assert_eq!(generate.archive().len(), 1);
+    }
+
+    #[test]
+    fn planning_an_unknown_value_adds_a_new_recipe() {
+        let mut generate = planner();
+        generate.set_seed(5);
+        let mut generate = generate.generate_days(false);
+        let previous = generate.days()[0].to_string();
+
+        generate.plan_entry(0, "Pannenkoeken").unwrap();
+
+        assert_eq!(generate.days()[0].to_string(), "Pannenkoeken");
+        assert_eq!(generate.days()[0].recipe().unwrap().id(), 10);
+        assert!(generate.pool().iter().any(|r| r.name() == previous));
+    }
+
+    #[test]
+    fn seasons_are_checked_against_the_planned_day() {
+        let mut recipes: Vec<Recipe> = ["lente", "zomer", "herfst", "winter"]
+            .into_iter()
+            .flat_map(|season| (0..100).map(move |n| format!("Dish {} ({})", n, season)))
+            .enumerate()
+            .map(|(id, name)| Recipe::new(id, name))
+            .collect();
+        qualifier::apply(recipes.iter_mut());
+        let mut settings = Settings::default();
+        // Long enough to always span two seasons
+        settings.set_horizon(100);
+        let mut generate = Generate::from_recipes(recipes, settings);
+        generate.set_seed(5);
+        let generate = generate.generate_days(false);
+
+        assert_eq!(generate.days().len(), 100);
+        for (index, slot) in generate.days().iter().enumerate() {
+            let recipe = slot.recipe().expect("every season has enough recipes");
+            assert!(Generate::in_season(recipe, generate.date_of(index)));
+        }
+    }
+
+    #[test]
+    fn rotate_returns_past_recipes_to_the_pool_without_filling() {
+        let dir = test_dir("rotate");
+        let (input, output) = (path(&dir, "input.txt"), path(&dir, "output.txt"));
+        let today = Generate::today();
+        fs::write(&input, "A\nB\nC\n").unwrap();
+        fs::write(
+            &output,
+            format!(
+                "{}\tPizza\n{}\tC\n{}\tA\n",
+                (today - Duration::days(2)).format(DATE_FORMAT),
+                (today - Duration::days(1)).format(DATE_FORMAT),
+                today.format(DATE_FORMAT)
+            ),
+        )
+        .unwrap();
+
+        let mut generate = Generate::read_entries(&input, &output, Settings::default(), false)
+            .expect("files are readable");
+        assert_eq!(generate.days().len(), 3);
+        assert_eq!(generate.rotate(), 2);
+
+        assert_eq!(generate.start(), today);
+        assert_eq!(generate.days().len(), 1);
+        let pool: Vec<&str> = generate.pool().iter().map(|r| r.name()).collect();
+        assert_eq!(pool, ["B", "C"]);
+        let yesterday = today - Duration::days(1);
+        assert_eq!(generate.history().last_planned("C", today), Some(yesterday));
+        fs::remove_dir_all(dir).unwrap();
     }
 }
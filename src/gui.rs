@@ -1,15 +1,24 @@
-use crate::generate::Generate;
-use chrono::prelude::*;
+use chrono::Weekday;
+use dinner_generator::config::Config;
+use dinner_generator::error::{Error, Result};
+use dinner_generator::generate::{Generate, Strategy};
+use dinner_generator::matching;
+use dinner_generator::recipe::{self, Recipe, DEFAULT_WEIGHT, FAVOURITE_WEIGHT, RARELY_WEIGHT};
+use dinner_generator::slot::SlotKind;
 use eframe::egui;
+use std::time::{Duration, Instant};
 
-const DAYS: [&str; 7] = [
-    "Monday",
-    "Tuesday",
-    "Wednesday",
-    "Thursday",
-    "Friday",
-    "Saturday",
-    "Sunday",
+/// How long an error stays on screen
+const TOAST_DURATION: Duration = Duration::from_secs(8);
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
 ];
 
 #[derive(Eq, PartialEq, Clone)]
@@ -17,7 +26,11 @@ enum ActiveModal {
     AddToPool,
     EditEntry(usize, bool),
     RemoveFromPool(usize),
+    RestoreFromArchive(usize),
     EditPoolEntry(usize, bool),
+    /// The value typed for a day only resembles pool recipes: the day, the matches and the
+    /// value as typed
+    ChooseRecipe(usize, Vec<(usize, String)>, String),
     None,
 }
 
@@ -25,26 +38,118 @@ enum ActiveState {
     Swapping { first_index: Option<usize> },
 }
 
+#[derive(Eq, PartialEq, Clone, Copy)]
+enum ActiveView {
+    Days,
+    Pool,
+    Archive,
+}
+
 pub struct DinnerViewer {
     entries: Generate,
     active_modal: ActiveModal,
     input_entry: String,
+    /// Tags of the pool entry being edited, separated by commas
+    input_tags: String,
     active_state: ActiveState,
-    showing_pool: bool,
-    current_day: Weekday,
+    active_view: ActiveView,
+    config: Config,
+    /// Whether the config file could not be read and the defaults are used instead, in which
+    /// case it is not overwritten
+    config_fallback: bool,
+    /// Whether the files could not be read and an empty plan is shown instead, in which case
+    /// the output file is not overwritten
+    entries_fallback: bool,
+    /// Errors shown as toasts, with the moment they occurred
+    errors: Vec<(String, Instant)>,
+    /// Options for shuffling the order of the days
+    keep_today: bool,
+    spread_tags: bool,
+    /// Filter of the pool and archive lists
+    search: String,
 }
 
 impl DinnerViewer {
-    pub fn new(g: Generate) -> Self {
+    pub fn new(g: Generate, config: Config) -> Self {
         DinnerViewer {
             entries: g,
-            ..Self::default()
+            active_modal: ActiveModal::None,
+            input_entry: "".to_owned(),
+            input_tags: String::new(),
+            active_state: ActiveState::Swapping { first_index: None },
+            active_view: ActiveView::Days,
+            config,
+            config_fallback: false,
+            entries_fallback: false,
+            errors: vec![],
+            keep_today: true,
+            spread_tags: true,
+            search: String::new(),
+        }
+    }
+
+    /// Shows the error of a failed action as a toast
+    fn report(&mut self, result: Result<()>) {
+        if let Err(e) = result {
+            self.errors.push((e.to_string(), Instant::now()));
+        }
+    }
+
+    fn save_days(&mut self) {
+        if self.entries_fallback {
+            return;
         }
+        let result = self.entries.write_file(self.config.output_file());
+        self.report(result);
     }
 
+    fn show_errors(&mut self, ctx: &egui::Context) {
+        self.errors
+            .retain(|(_, since)| since.elapsed() < TOAST_DURATION);
+        if self.errors.is_empty() {
+            return;
+        }
+        let mut dismissed = None;
+        egui::Area::new(egui::Id::new("errors"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-10.0, -10.0))
+            .show(ctx, |ui| {
+                for (index, (error, _)) in self.errors.iter().enumerate() {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.colored_label(ui.visuals().error_fg_color, error);
+                        if ui.small_button("Dismiss").clicked() {
+                            dismissed = Some(index);
+                        }
+                    });
+                }
+            });
+        if let Some(index) = dismissed {
+            self.errors.remove(index);
+        }
+        ctx.request_repaint_after(Duration::from_secs(1));
+    }
+
+    /// Drops the days which have passed, also when the app was left open past midnight, and
+    /// fills the new days at the end
     fn rotate_entries(&mut self) {
-        self.entries.remove_from_days(0);
-        let _ = self.entries.regenerate_entry(self.entries.days().len() - 1);
+        if self.entries.rotate() > 0 {
+            self.entries.fill_unplanned();
+            self.save_days();
+        }
+    }
+
+    /// Search box above the pool and archive, returning the indices of the recipes to list
+    fn search(&mut self, ui: &mut egui::Ui, recipes: &[Recipe]) -> Vec<usize> {
+        ui.horizontal(|ui| {
+            ui.label("Search:");
+            ui.text_edit_singleline(&mut self.search);
+            if !self.search.is_empty() && ui.small_button("Clear").clicked() {
+                self.search.clear();
+            }
+        });
+        if self.search.trim().is_empty() {
+            return (0..recipes.len()).collect();
+        }
+        matching::search(&self.search, recipes.iter().map(|r| r.name()))
     }
 
     fn view_pool(&mut self, ui: &mut egui::Ui) {
@@ -57,14 +162,22 @@ impl DinnerViewer {
             .auto_shrink(false);
 
         let mut string_set = false;
+        let pool = self.entries.pool().clone();
+        let listed = self.search(ui, &pool);
         ui.separator();
         scroll_area.show(ui, |ui| {
-            for (index, entry) in self.entries.pool().clone().iter().enumerate() {
+            for (index, entry) in listed.into_iter().map(|index| (index, &pool[index])) {
+                let label = if entry.tags().is_empty() {
+                    entry.name().to_owned()
+                } else {
+                    format!("{} [{}]", entry.name(), entry.tags().join(", "))
+                };
                 ui.vertical(|ui| {
-                    ui.menu_button(entry, |ui| {
+                    ui.menu_button(label, |ui| {
                         if ui.button("Edit").clicked() {
                             if !string_set {
-                                self.input_entry = entry.to_owned();
+                                self.input_entry = entry.name().to_owned();
+                                self.input_tags = entry.tags().join(", ");
                                 string_set = true;
                             }
 
@@ -73,6 +186,25 @@ impl DinnerViewer {
                         if ui.button("Remove").clicked() {
                             self.active_modal = ActiveModal::RemoveFromPool(index);
                         }
+                        ui.menu_button(
+                            format!("Weight: {}", recipe::weight_label(entry.weight())),
+                            |ui| {
+                                for (label, weight) in [
+                                    ("Favourite", FAVOURITE_WEIGHT),
+                                    ("Normal", DEFAULT_WEIGHT),
+                                    ("Rarely", RARELY_WEIGHT),
+                                ] {
+                                    if ui.button(label).clicked() {
+                                        let result = self.entries.set_pool_weight(index, weight);
+                                        self.report(result);
+                                        ui.close_menu();
+                                    }
+                                }
+                            },
+                        );
+                        if let Some(source) = entry.source() {
+                            ui.hyperlink_to("Open recipe", source);
+                        }
                     });
                 });
             }
@@ -81,12 +213,35 @@ impl DinnerViewer {
         if ui.button("Add entry").clicked() {
             self.active_modal = ActiveModal::AddToPool;
         }
-        let current_day = Local::now().weekday();
-        if current_day != self.current_day {
-            println!("setting current day to {:?}", current_day);
-            self.rotate_entries();
-            self.current_day = current_day;
-        }
+    }
+
+    fn view_archive(&mut self, ui: &mut egui::Ui) {
+        let style = ui.style_mut();
+        style.spacing.button_padding = egui::vec2(30.0, 10.0); // Adjust padding (influences size)
+        style.visuals.widgets.active.rounding = egui::Rounding::same(45.0); // Optional rounding for button
+
+        let scroll_area = egui::ScrollArea::vertical()
+            .max_height(500.0)
+            .auto_shrink(false);
+
+        let archive = self.entries.archive().clone();
+        let listed = self.search(ui, &archive);
+        ui.separator();
+        scroll_area.show(ui, |ui| {
+            for (index, entry) in listed.into_iter().map(|index| (index, &archive[index])) {
+                ui.vertical(|ui| {
+                    ui.menu_button(entry.name(), |ui| {
+                        if ui.button("Restore").clicked() {
+                            self.active_modal = ActiveModal::RestoreFromArchive(index);
+                        }
+                        if let Some(source) = entry.source() {
+                            ui.hyperlink_to("Open recipe", source);
+                        }
+                    });
+                });
+            }
+        });
+        ui.separator();
     }
 
     fn view_days(&mut self, ui: &mut egui::Ui) {
@@ -98,22 +253,32 @@ impl DinnerViewer {
             .max_height(600.0)
             .auto_shrink(true);
 
-        let current_day_index = self.current_day.num_days_from_monday();
-
         let mut string_set = false;
         ui.separator();
         scroll_area.show(ui, |ui| {
             for (index, day) in self.entries.days().clone().iter().enumerate() {
-                let day_index = (current_day_index as usize + index) % DAYS.len();
-                let weekday = DAYS[day_index];
+                let date = self.entries.date_of(index);
+                if index > 0 && self.entries.settings().starts_week(date) {
+                    ui.separator();
+                }
+                let weekday = date.format("%a %-d %b");
+                let locked = self.entries.is_locked(index);
+                let label = if locked {
+                    format!("🔒 {}: {}", weekday, day)
+                } else {
+                    format!("{}: {}", weekday, day)
+                };
                 ui.vertical(|ui| {
-                    ui.menu_button(format!("{}: {}", weekday, day), |ui| {
+                    ui.menu_button(label, |ui| {
                         if ui.button("Regenerate Entry").clicked() {
-                            let _ = self.entries.regenerate_entry(index);
+                            let result = self.entries.regenerate_entry(index);
+                            self.report(result);
+                            self.save_days();
                         }
                         if ui.button("Edit entry").clicked() {
                             if !string_set {
-                                self.input_entry = day.to_owned().to_owned();
+                                self.input_entry =
+                                    day.recipe().map_or(String::new(), |r| r.name().to_owned());
                                 string_set = true;
                             }
                             self.active_modal = ActiveModal::EditEntry(index, string_set);
@@ -130,58 +295,214 @@ impl DinnerViewer {
                                     first_index: Some(first_index),
                                 } => {
                                     // Perform the swap
-                                    let _ = self.entries.swap_days_entries(*first_index, index);
+                                    let result =
+                                        self.entries.swap_days_entries(*first_index, index);
+                                    self.report(result);
+                                    self.save_days();
                                     // Reset the state to Normal after swap
                                     self.active_state = ActiveState::Swapping { first_index: None };
                                     ui.close_menu();
                                 }
                             }
                         }
+                        if ui.button(if locked { "Unlock" } else { "Lock" }).clicked() {
+                            let result = self.entries.set_locked(index, !locked);
+                            self.report(result);
+                            self.save_days();
+                            ui.close_menu();
+                        }
+                        ui.menu_button("Mark as", |ui| {
+                            for (label, kind) in [
+                                ("Skip", SlotKind::Skip),
+                                ("Eating out", SlotKind::EatOut),
+                                ("Leftovers", SlotKind::Leftovers),
+                                ("Unplanned", SlotKind::Unplanned),
+                            ] {
+                                if ui.button(label).clicked() {
+                                    let result = self.entries.set_day_slot(index, kind);
+                                    self.report(result);
+                                    self.save_days();
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                        if let Some(source) = day.recipe().and_then(|r| r.source()) {
+                            ui.hyperlink_to("Open recipe", source);
+                        }
                     });
                 });
             }
         });
         ui.separator();
-        if ui.button("Regenerate").clicked() {
-            self.entries = self.entries.generate_days(self.entries.days().len(), true);
+        ui.horizontal(|ui| {
+            if ui.button("Regenerate").clicked() {
+                self.entries = self.entries.generate_days(true);
+                self.save_days();
+            }
+            if ui.button("Fill empty").clicked() {
+                self.entries.fill_unplanned();
+                self.save_days();
+            }
+            if ui.button("Shuffle order").clicked() {
+                self.entries
+                    .randomize_days(self.keep_today, self.spread_tags);
+                self.save_days();
+            }
+            ui.checkbox(&mut self.keep_today, "Keep today");
+            ui.checkbox(&mut self.spread_tags, "Spread tags");
+        });
+        ui.horizontal(|ui| {
+            let mut strategy = self.entries.strategy();
+            ui.label("Strategy:");
+            ui.radio_value(&mut strategy, Strategy::Random, "Random");
+            ui.radio_value(&mut strategy, Strategy::Recency, "Recency");
+            self.entries.set_strategy(strategy);
+        });
+        ui.collapsing("Plan settings", |ui| {
+            let mut settings = self.entries.settings().clone();
+            ui.horizontal(|ui| {
+                let mut horizon = settings.horizon();
+                ui.label("Days to plan:");
+                ui.add(egui::DragValue::new(&mut horizon).range(1..=31));
+                settings.set_horizon(horizon);
+            });
+            ui.horizontal(|ui| {
+                let mut cooldown_days = settings.cooldown_days();
+                ui.label("Cooldown days:");
+                ui.add(egui::DragValue::new(&mut cooldown_days).range(0..=365));
+                settings.set_cooldown_days(cooldown_days);
+            });
+            ui.horizontal(|ui| {
+                let mut week_start = settings.week_start();
+                ui.label("Week starts on:");
+                egui::ComboBox::from_id_salt("week_start")
+                    .selected_text(week_start.to_string())
+                    .show_ui(ui, |ui| {
+                        for weekday in WEEKDAYS {
+                            ui.selectable_value(&mut week_start, weekday, weekday.to_string());
+                        }
+                    });
+                settings.set_week_start(week_start);
+            });
+            ui.horizontal(|ui| {
+                let mut weekdays = settings.weekdays().clone();
+                for weekday in WEEKDAYS {
+                    let mut planned = weekdays.contains(&weekday);
+                    if ui.checkbox(&mut planned, weekday.to_string()).changed() {
+                        weekdays.retain(|&day| day != weekday);
+                        if planned {
+                            weekdays.push(weekday);
+                        }
+                    }
+                }
+                settings.set_weekdays(weekdays);
+            });
+            if &settings != self.entries.settings() {
+                self.entries.set_settings(settings.clone());
+                self.config.set_plan(settings);
+                if !self.config_fallback {
+                    let result = self.config.save();
+                    self.report(result);
+                }
+            }
+        });
+        if !self.entries.relaxed().is_empty() {
+            ui.label(format!(
+                "Cooldown relaxed for: {}",
+                self.entries.relaxed().join(", ")
+            ));
         }
     }
 
     fn show_modal(&mut self, ctx: &egui::Context) {
         let mut entries = self.entries.clone();
-        match self.active_modal {
+        let mut result = Ok(());
+        let mut save = false;
+        match self.active_modal.clone() {
             ActiveModal::AddToPool => {
                 self.open_window("add_to_pool", "Add entry to pool", ctx, |entry| {
-                    entries.add_to_pool(entry);
+                    result = entries.add_to_pool(entry);
                 })
             }
             ActiveModal::EditPoolEntry(index, mut set) => {
-                self.open_window("edit_entry", "Edit day entry", ctx, |entry| {
-                    let _ = entries.edit_pool_entry(index, entry);
+                let tags = recipe::parse_tags(&self.input_tags);
+                self.open_window("edit_entry", "Edit pool entry", ctx, |entry| {
+                    result = entries
+                        .edit_pool_entry(index, entry)
+                        .and_then(|_| entries.set_pool_tags(index, tags));
                     set = false;
                 })
             }
             ActiveModal::EditEntry(index, mut set) => {
+                let mut choice = None;
                 self.open_window("edit_entry", "Edit day entry", ctx, |entry| {
-                    let _ = entries.edit_days_entry(index, entry);
+                    result = match entries.plan_entry(index, &entry) {
+                        Err(Error::Ambiguous { candidates, .. }) => {
+                            choice = Some(ActiveModal::ChooseRecipe(index, candidates, entry));
+                            Ok(())
+                        }
+                        planned => {
+                            save = true;
+                            planned
+                        }
+                    };
                     set = false;
-                })
+                });
+                if let Some(choice) = choice {
+                    self.active_modal = choice;
+                }
+            }
+            ActiveModal::ChooseRecipe(index, candidates, typed) => {
+                let mut chosen = None;
+                egui::Window::new("choose_recipe").show(ctx, |ui| {
+                    ui.label(format!("{} could mean:", typed));
+                    for (pos, name) in &candidates {
+                        if ui.button(name).clicked() {
+                            chosen = Some(entries.plan_pool_entry(index, *pos));
+                        }
+                    }
+                    if ui.button(format!("Add \"{}\" as typed", typed)).clicked() {
+                        chosen = Some(entries.edit_days_entry(index, typed.clone()));
+                    }
+                    if ui.button("Close").clicked() {
+                        self.active_modal = ActiveModal::None;
+                    }
+                });
+                if let Some(planned) = chosen {
+                    result = planned;
+                    save = true;
+                    self.active_modal = ActiveModal::None;
+                }
             }
             ActiveModal::RemoveFromPool(index) => {
-                let _ = entries.remove_from_pool(index, "input.txt");
+                result = entries.remove_from_pool(index);
+                self.active_modal = ActiveModal::None;
+            }
+            ActiveModal::RestoreFromArchive(index) => {
+                result = entries.restore_from_archive(index);
+                self.active_modal = ActiveModal::None;
             }
             ActiveModal::None => (),
         }
         self.entries = entries;
+        if save && result.is_ok() {
+            self.save_days();
+        }
+        self.report(result);
     }
 
     fn open_window<F>(&mut self, title: &str, label: &str, ctx: &egui::Context, on_submit: F)
     where
         F: FnOnce(String),
     {
+        let edits_tags = matches!(self.active_modal, ActiveModal::EditPoolEntry(..));
         egui::Window::new(title).show(ctx, |ui| {
             ui.label(label);
             ui.text_edit_singleline(&mut self.input_entry);
+            if edits_tags {
+                ui.label("Tags, separated by commas");
+                ui.text_edit_singleline(&mut self.input_tags);
+            }
 
             if ui.button("Submit").clicked() && !self.input_entry.is_empty() {
                 let entry = self.input_entry.clone(); // Take ownership of input
@@ -196,44 +517,88 @@ impl DinnerViewer {
         });
     }
 }
+/// Opens the frontend window
+pub fn run() -> eframe::Result {
+    let native_options = eframe::NativeOptions::default();
+
+    eframe::run_native(
+        "Days Viewer", // Window title
+        native_options,
+        Box::new(|_| Ok(Box::new(DinnerViewer::default()))),
+    )
+}
+
 impl Default for DinnerViewer {
+    /// Opens the files from the config, creating them on first launch. Errors are shown once
+    /// the window is open, with an empty plan if the files could not be read.
     fn default() -> Self {
-        Self {
-            entries: Generate::read_entries("input.txt", "output.txt", 7, false).unwrap(),
-            active_modal: ActiveModal::None,
-            input_entry: "".to_owned(),
-            active_state: ActiveState::Swapping { first_index: None },
-            showing_pool: false,
-            current_day: Local::now().weekday(),
+        let mut errors = vec![];
+        let (config, config_fallback) = match Config::load() {
+            Ok(config) => (config, false),
+            Err(e) => {
+                errors.push(Err(e));
+                (Config::default(), true)
+            }
+        };
+        errors.push(config.prepare_files());
+        let mut entries_fallback = false;
+        let entries = match Generate::read_entries(
+            config.input_file(),
+            config.output_file(),
+            config.plan().clone(),
+            false,
+        ) {
+            Ok(mut entries) => {
+                entries.rotate();
+                entries.fill_unplanned();
+                errors.push(entries.write_file(config.output_file()));
+                entries
+            }
+            Err(e) => {
+                errors.push(Err(e));
+                entries_fallback = true;
+                Generate::from_recipes(vec![], config.plan().clone())
+            }
+        };
+        let mut viewer = Self::new(entries, config);
+        viewer.config_fallback = config_fallback;
+        viewer.entries_fallback = entries_fallback;
+        for result in errors {
+            viewer.report(result);
         }
+        viewer
     }
 }
 
 impl eframe::App for DinnerViewer {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.rotate_entries();
+        ctx.request_repaint_after(Duration::from_secs(60));
         egui::CentralPanel::default().show(ctx, |ui| {
             ctx.set_pixels_per_point(1.5);
             ui.heading("dinner viewer");
-            if self.showing_pool {
-                self.view_pool(ui);
-            } else {
-                self.view_days(ui);
+            match self.active_view {
+                ActiveView::Days => self.view_days(ui),
+                ActiveView::Pool => self.view_pool(ui),
+                ActiveView::Archive => self.view_archive(ui),
             }
 
-            if ui
-                .button(if self.showing_pool {
-                    "View Days"
-                } else {
-                    "View Pool"
-                })
-                .clicked()
-            {
-                self.showing_pool = !self.showing_pool;
-            }
+            ui.horizontal(|ui| {
+                for (view, label) in [
+                    (ActiveView::Days, "View Days"),
+                    (ActiveView::Pool, "View Pool"),
+                    (ActiveView::Archive, "Archived"),
+                ] {
+                    if self.active_view != view && ui.button(label).clicked() {
+                        self.active_view = view;
+                    }
+                }
+            });
 
             if self.active_modal != ActiveModal::None {
                 self.show_modal(ctx);
             }
         });
+        self.show_errors(ctx);
     }
 }
@@ -0,0 +1,81 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use chrono::NaiveDate;
+
+use crate::error::Result;
+use crate::file_utils;
+
+/// Format of the dates in the history and output files
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Dated record of planned meals, stored as `<date>\t<recipe>` lines next to the output file.
+/// Planning a date again replaces the meal recorded for it.
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    path: Option<String>,
+    entries: BTreeMap<NaiveDate, String>,
+}
+
+impl History {
+    /// Reads the history belonging to the given output file, a missing file yields an empty
+    /// history
+    pub fn load(outputfile: &str) -> Result<Self> {
+        let path = Path::new(outputfile)
+            .with_file_name("history.txt")
+            .to_string_lossy()
+            .into_owned();
+        let mut entries = BTreeMap::new();
+        if Path::new(&path).exists() {
+            for line in file_utils::read_file(&path)? {
+                if let Some((date, name)) = line.split_once('\t') {
+                    if let Ok(date) = NaiveDate::parse_from_str(date, DATE_FORMAT) {
+                        entries.insert(date, name.to_owned());
+                    }
+                }
+            }
+        }
+        Ok(Self {
+            path: Some(path),
+            entries,
+        })
+    }
+
+    pub fn entries(&self) -> &BTreeMap<NaiveDate, String> {
+        &self.entries
+    }
+
+    pub fn record(&mut self, date: NaiveDate, name: &str) {
+        self.entries.insert(date, name.to_owned());
+    }
+
+    pub fn forget(&mut self, date: NaiveDate) {
+        self.entries.remove(&date);
+    }
+
+    /// Forgets all meals planned on or after the given date
+    pub fn forget_from(&mut self, date: NaiveDate) {
+        self.entries.split_off(&date);
+    }
+
+    /// Returns the most recent date before `before` on which the recipe was planned
+    pub fn last_planned(&self, name: &str, before: NaiveDate) -> Option<NaiveDate> {
+        self.entries
+            .range(..before)
+            .rev()
+            .find(|(_, entry)| *entry == name)
+            .map(|(date, _)| *date)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(path) = &self.path {
+            let lines: Vec<String> = self
+                .entries
+                .iter()
+                .map(|(date, name)| format!("{}\t{}", date.format(DATE_FORMAT), name))
+                .collect();
+            file_utils::write_file(&lines, path)?;
+        }
+        Ok(())
+    }
+}
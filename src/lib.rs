@@ -0,0 +1,45 @@
+//! Dinner planner which picks recipes from a pool for the coming days.
+//!
+//! [`generate::Generate`] holds the plan, the pool of recipes it picks from and the archive of
+//! disabled recipes. It is usually read from an input file with one recipe per line and an
+//! output file holding the plan with [`generate::Generate::read_entries`], but can also be built
+//! from recipes in memory:
+//!
+//! ```
+//! use dinner_generator::generate::Generate;
+//! use dinner_generator::recipe::Recipe;
+//! use dinner_generator::settings::Settings;
+//!
+//! let recipes = vec![
+//!     Recipe::new(0, "Lasagne".to_owned()),
+//!     Recipe::new(1, "Pasta pesto".to_owned()),
+//! ];
+//! let mut settings = Settings::default();
+//! settings.set_horizon(2);
+//! let mut planner = Generate::from_recipes(recipes, settings);
+//! planner.set_seed(42);
+//! planner.fill_unplanned();
+//! assert_eq!(planner.days().len(), 2);
+//! ```
+
+/// User configuration with the data paths and plan defaults
+pub mod config;
+/// Reading and editing the input file while keeping its comments and layout
+pub mod document;
+/// Errors of the planner and its storage
+pub mod error;
+mod file_utils;
+/// The planner: selection of recipes and editing of the plan and the pool
+pub mod generate;
+/// Record of past meals, used for the cooldown and recency
+pub mod history;
+/// Accent and case insensitive fuzzy matching of entry names
+pub mod matching;
+/// Seasons, optional components and variants read from parentheses in recipe names
+pub mod qualifier;
+/// The recipe model
+pub mod recipe;
+/// Plan length and planned weekdays
+pub mod settings;
+/// What is planned for a single day
+pub mod slot;
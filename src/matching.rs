@@ -0,0 +1,180 @@
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Score from 0 to 1 from which a name counts as matching a query
+const MATCH_SCORE: f64 = 0.8;
+/// Score from 0 to 1 from which a name is suggested for a query which matched nothing
+const CLOSE_MATCH_SCORE: f64 = 0.5;
+/// Score of a name containing all words of the query
+const WORDS_SCORE: f64 = 0.9;
+/// Shortest query which is also compared against the start of names, so typos in the first
+/// words of a long name are found
+const PREFIX_MIN_LENGTH: usize = 4;
+/// Highest score of a name whose start resembles the query, below [`WORDS_SCORE`] so a name
+/// containing the query as typed wins
+const PREFIX_SCORE: f64 = 0.85;
+
+/// Outcome of looking a query up among names
+#[derive(Debug, Clone, PartialEq)]
+pub enum Lookup {
+    /// A single name matches, by its index
+    Found(usize),
+    /// Several names match equally well, best first
+    Ambiguous(Vec<usize>),
+    /// No name matches, with the indices of similar names, most similar first
+    Missing(Vec<usize>),
+}
+
+/// Folds a name for comparison: accents are stripped, case is ignored, apostrophes are dropped
+/// and other punctuation counts as a space, so `Maïskolven`, `maiskolven` and `Taco's`,
+/// `tacos` compare equal
+pub fn normalize(text: &str) -> String {
+    let folded: String = text
+        .nfkd()
+        .filter(|&c| !is_combining_mark(c))
+        .flat_map(char::to_lowercase)
+        .filter(|&c| !matches!(c, '\'' | '’' | '`'))
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect();
+    folded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Whether two names are the same after [`normalize`]
+pub fn same(a: &str, b: &str) -> bool {
+    normalize(a) == normalize(b)
+}
+
+/// Similarity of a name to a query from 0 to 1, where 1 means they are the same after
+/// [`normalize`]. An empty query matches nothing.
+pub fn score(query: &str, name: &str) -> f64 {
+    let query = normalize(query);
+    let name = normalize(name);
+    if query.is_empty() {
+        return 0.0;
+    }
+    if query == name {
+        return 1.0;
+    }
+    let words: Vec<&str> = name.split(' ').collect();
+    if query
+        .split(' ')
+        .all(|part| words.iter().any(|word| word.starts_with(part)))
+    {
+        return WORDS_SCORE;
+    }
+    let whole = strsim::normalized_damerau_levenshtein(&query, &name);
+    let length = query.chars().count();
+    if length < PREFIX_MIN_LENGTH {
+        return whole;
+    }
+    let start: String = name.chars().take(length).collect();
+    let prefix = strsim::normalized_damerau_levenshtein(&query, &start).min(PREFIX_SCORE);
+    whole.max(prefix)
+}
+
+/// Looks a query up among names. A name which is the same as the query wins, otherwise all
+/// names scoring at least [`MATCH_SCORE`] are candidates and the best of them wins if it is the
+/// only one with its score.
+pub fn find<'a>(query: &str, names: impl IntoIterator<Item = &'a str>) -> Lookup {
+    let scored = ranked(query, names);
+    let Some(&(_, best)) = scored.first() else {
+        return Lookup::Missing(vec![]);
+    };
+    if best < MATCH_SCORE {
+        return Lookup::Missing(scored.into_iter().map(|(index, _)| index).collect());
+    }
+    let tied: Vec<usize> = scored
+        .iter()
+        .take_while(|&&(_, score)| score == best)
+        .map(|&(index, _)| index)
+        .collect();
+    if tied.len() == 1 {
+        return Lookup::Found(tied[0]);
+    }
+    if best == 1.0 {
+        return Lookup::Ambiguous(tied);
+    }
+    Lookup::Ambiguous(
+        scored
+            .into_iter()
+            .take_while(|&(_, score)| score >= MATCH_SCORE)
+            .map(|(index, _)| index)
+            .collect(),
+    )
+}
+
+/// Indices of the names which resemble the query, most similar first, for filtering a list
+pub fn search<'a>(query: &str, names: impl IntoIterator<Item = &'a str>) -> Vec<usize> {
+    ranked(query, names)
+        .into_iter()
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Indices and scores of the names scoring at least [`CLOSE_MATCH_SCORE`], best first
+fn ranked<'a>(query: &str, names: impl IntoIterator<Item = &'a str>) -> Vec<(usize, f64)> {
+    let mut scored: Vec<(usize, f64)> = names
+        .into_iter()
+        .enumerate()
+        .map(|(index, name)| (index, score(query, name)))
+        .filter(|&(_, score)| score >= CLOSE_MATCH_SCORE)
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMES: [&str; 5] = [
+        "Maïskolven",
+        "Taco's",
+        "Pad thai",
+        "Pasta pesto",
+        "Pasta carbonara",
+    ];
+
+    #[test]
+    fn folds_accents_case_and_punctuation() {
+        assert_eq!(normalize("  Maïskolven "), "maiskolven");
+        assert_eq!(normalize("Taco's"), "tacos");
+        assert_eq!(normalize("Chili-con-carne!"), "chili con carne");
+        assert!(same("PAD THAI", "pad  thai"));
+    }
+
+    #[test]
+    fn finds_names_regardless_of_accents_and_case() {
+        assert_eq!(find("maiskolven", NAMES), Lookup::Found(0));
+        assert_eq!(find("TACOS", NAMES), Lookup::Found(1));
+    }
+
+    #[test]
+    fn finds_names_with_typos() {
+        assert_eq!(find("pad thia", NAMES), Lookup::Found(2));
+        assert_eq!(find("pasta carbonarra", NAMES), Lookup::Found(4));
+    }
+
+    #[test]
+    fn prefers_exact_names_over_partial_ones() {
+        let names = ["Pasta", "Pasta pesto"];
+        assert_eq!(find("pasta", names), Lookup::Found(0));
+        assert_eq!(find("pesto", names), Lookup::Found(1));
+    }
+
+    #[test]
+    fn reports_several_equally_good_matches() {
+        assert_eq!(find("pasta", NAMES), Lookup::Ambiguous(vec![3, 4]));
+        assert_eq!(
+            find("soup", ["Soup", "soup"]),
+            Lookup::Ambiguous(vec![0, 1])
+        );
+    }
+
+    #[test]
+    fn suggests_similar_names_when_nothing_matches() {
+        assert_eq!(find("pasta pomodoro", NAMES), Lookup::Missing(vec![3, 4]));
+        assert_eq!(find("stamppot", NAMES), Lookup::Missing(vec![]));
+        assert_eq!(find("", NAMES), Lookup::Missing(vec![]));
+    }
+}
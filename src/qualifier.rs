@@ -0,0 +1,292 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::matching;
+use crate::recipe::Recipe;
+
+/// Words joining several seasons in one qualifier, e.g. `(herfst en winter)`
+const SEASON_JOINERS: [&str; 4] = ["en", "of", "and", "or"];
+
+/// Season of the year, for recipes which are only cooked in some of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    /// Meteorological season of a date on the northern hemisphere
+    pub fn of(date: NaiveDate) -> Self {
+        match date.month() {
+            3..=5 => Season::Spring,
+            6..=8 => Season::Summer,
+            9..=11 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+
+    /// Reads a season in Dutch or English, e.g. `winter`, `zomer` or `autumn`
+    fn parse(word: &str) -> Option<Self> {
+        match word {
+            "lente" | "voorjaar" | "spring" => Some(Season::Spring),
+            "zomer" | "summer" => Some(Season::Summer),
+            "herfst" | "najaar" | "autumn" | "fall" => Some(Season::Autumn),
+            "winter" => Some(Season::Winter),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Season {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Season::Spring => "spring",
+            Season::Summer => "summer",
+            Season::Autumn => "autumn",
+            Season::Winter => "winter",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Meaning of the parentheses in a recipe name: `Stamppot(winter)` is only cooked in winter,
+/// `(Kip met) perziken` has an optional component and `Rijst (thai curry)` is a variant of
+/// `Rijst` if another entry such as `Rijst (zoetzuur)` shares that base.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Qualifiers {
+    /// The name without its parentheses
+    base: String,
+    /// Seasons the recipe is cooked in, empty for all year
+    seasons: Vec<Season>,
+    /// Leading parentheses, a part of the dish which may be left out
+    optional: Option<String>,
+    /// Trailing parentheses which are not a season
+    detail: Option<String>,
+    /// Whether `detail` names one of several variants of `base`, see [`apply`]
+    variant: bool,
+    /// Parentheses which could not be interpreted
+    unrecognised: Vec<String>,
+}
+
+impl Qualifiers {
+    /// Reads the parentheses of a single name. Whether its detail is a variant depends on the
+    /// other names, which [`apply`] takes care of.
+    pub fn interpret(name: &str) -> Self {
+        let mut qualifiers = Self::default();
+        let mut outside = String::new();
+        let mut depth = 0;
+        let mut start = 0;
+        for (index, c) in name.char_indices() {
+            match c {
+                '(' => {
+                    if depth == 0 {
+                        start = index;
+                    }
+                    depth += 1;
+                }
+                ')' if depth > 0 => {
+                    depth -= 1;
+                    if depth == 0 {
+                        let end = index + c.len_utf8();
+                        qualifiers.read_group(name, start, end);
+                        outside.push(' ');
+                    }
+                }
+                c if depth == 0 => outside.push(c),
+                _ => (),
+            }
+        }
+        if depth > 0 {
+            return Self {
+                base: name.trim().to_owned(),
+                unrecognised: vec!["unbalanced parentheses".to_owned()],
+                ..Self::default()
+            };
+        }
+        qualifiers.base = outside.split_whitespace().collect::<Vec<_>>().join(" ");
+        if qualifiers.base.is_empty() {
+            // The whole name is in parentheses, which leaves nothing to qualify
+            return Self {
+                base: name.trim().to_owned(),
+                ..Self::default()
+            };
+        }
+        qualifiers
+    }
+
+    /// Classifies the parentheses spanning `start..end` of the name
+    fn read_group(&mut self, name: &str, start: usize, end: usize) {
+        let content = name[start + 1..end - 1].trim().to_owned();
+        let words: Vec<String> = matching::normalize(&content)
+            .split(' ')
+            .filter(|word| !SEASON_JOINERS.contains(word))
+            .map(str::to_owned)
+            .collect();
+        let seasons: Option<Vec<Season>> = words.iter().map(|word| Season::parse(word)).collect();
+        match seasons {
+            Some(seasons) if !seasons.is_empty() => self.seasons.extend(seasons),
+            _ if name[..start].trim().is_empty() => self.optional = Some(content),
+            _ if name[end..].trim().is_empty() => self.detail = Some(content),
+            _ => self.unrecognised.push(content),
+        }
+    }
+
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    pub fn seasons(&self) -> &Vec<Season> {
+        &self.seasons
+    }
+
+    pub fn optional(&self) -> Option<&str> {
+        self.optional.as_deref()
+    }
+
+    pub fn detail(&self) -> Option<&str> {
+        self.detail.as_deref()
+    }
+
+    pub fn is_variant(&self) -> bool {
+        self.variant
+    }
+
+    pub fn unrecognised(&self) -> &Vec<String> {
+        &self.unrecognised
+    }
+
+    /// Key shared by all variants of the same base, None if the recipe is not a variant
+    pub fn variant_group(&self) -> Option<String> {
+        self.variant.then(|| matching::normalize(&self.base))
+    }
+
+    /// Whether the recipe may be cooked on the given date
+    pub fn in_season(&self, date: NaiveDate) -> bool {
+        self.seasons.is_empty() || self.seasons.contains(&Season::of(date))
+    }
+}
+
+impl fmt::Display for Qualifiers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = vec![];
+        if !self.seasons.is_empty() {
+            let seasons: Vec<String> = self.seasons.iter().map(Season::to_string).collect();
+            parts.push(format!("{} only", seasons.join(" and ")));
+        }
+        if let Some(optional) = &self.optional {
+            parts.push(format!("optionally {}", optional));
+        }
+        match &self.detail {
+            Some(detail) if self.variant => {
+                parts.push(format!("variant {} of {}", detail, self.base))
+            }
+            Some(detail) => parts.push(format!("note {}", detail)),
+            None => (),
+        }
+        for unrecognised in &self.unrecognised {
+            parts.push(format!("not understood: {}", unrecognised));
+        }
+        if parts.is_empty() {
+            write!(f, "plain name")
+        } else {
+            write!(f, "{}", parts.join(", "))
+        }
+    }
+}
+
+/// Interprets the names of the recipes, marking trailing details as variants where several
+/// recipes share a base
+pub fn apply<'a>(recipes: impl IntoIterator<Item = &'a mut Recipe>) {
+    let recipes: Vec<(&mut Recipe, Qualifiers)> = recipes
+        .into_iter()
+        .map(|recipe| {
+            let qualifiers = Qualifiers::interpret(recipe.name());
+            (recipe, qualifiers)
+        })
+        .collect();
+    let mut details: BTreeMap<String, usize> = BTreeMap::new();
+    for (_, qualifiers) in &recipes {
+        if qualifiers.detail.is_some() {
+            *details
+                .entry(matching::normalize(&qualifiers.base))
+                .or_default() += 1;
+        }
+    }
+    for (recipe, mut qualifiers) in recipes {
+        qualifiers.variant = qualifiers.detail.is_some()
+            && details.get(&matching::normalize(&qualifiers.base)) > Some(&1);
+        recipe.set_qualifiers(Some(qualifiers));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(month: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, month, 15).unwrap()
+    }
+
+    #[test]
+    fn reads_seasons() {
+        let qualifiers = Qualifiers::interpret("Stamppot(winter)");
+        assert_eq!(qualifiers.base(), "Stamppot");
+        assert_eq!(qualifiers.seasons(), &vec![Season::Winter]);
+        assert!(qualifiers.in_season(date(1)));
+        assert!(!qualifiers.in_season(date(7)));
+
+        let qualifiers = Qualifiers::interpret("Erwtensoep (herfst en Winter)");
+        assert_eq!(qualifiers.seasons(), &vec![Season::Autumn, Season::Winter]);
+        assert_eq!(qualifiers.to_string(), "autumn and winter only");
+        assert!(Qualifiers::interpret("Pasta").in_season(date(7)));
+    }
+
+    #[test]
+    fn reads_optional_components_and_details() {
+        let qualifiers = Qualifiers::interpret("(Kip met) perziken");
+        assert_eq!(qualifiers.base(), "perziken");
+        assert_eq!(qualifiers.optional(), Some("Kip met"));
+        assert_eq!(qualifiers.detail(), None);
+
+        let qualifiers = Qualifiers::interpret("Rijst (thai curry)");
+        assert_eq!(qualifiers.base(), "Rijst");
+        assert_eq!(qualifiers.detail(), Some("thai curry"));
+        assert!(!qualifiers.is_variant());
+        assert_eq!(qualifiers.to_string(), "note thai curry");
+    }
+
+    #[test]
+    fn keeps_what_it_cannot_interpret() {
+        let qualifiers = Qualifiers::interpret("Soep (tomaat) met brood");
+        assert_eq!(qualifiers.base(), "Soep met brood");
+        assert_eq!(qualifiers.unrecognised(), &vec!["tomaat".to_owned()]);
+
+        let qualifiers = Qualifiers::interpret("Soep (tomaat");
+        assert_eq!(qualifiers.base(), "Soep (tomaat");
+        assert_eq!(qualifiers.unrecognised().len(), 1);
+
+        let qualifiers = Qualifiers::interpret("(Verrassing)");
+        assert_eq!(qualifiers.base(), "(Verrassing)");
+        assert_eq!(qualifiers.to_string(), "plain name");
+    }
+
+    #[test]
+    fn marks_details_sharing_a_base_as_variants() {
+        let mut recipes: Vec<Recipe> = ["Rijst (thai curry)", "rijst (zoetzuur)", "Wraps (kip)"]
+            .into_iter()
+            .enumerate()
+            .map(|(id, name)| Recipe::new(id, name.to_owned()))
+            .collect();
+        apply(recipes.iter_mut());
+
+        let qualifiers: Vec<&Qualifiers> = recipes.iter().filter_map(Recipe::qualifiers).collect();
+        assert_eq!(qualifiers[0].variant_group(), Some("rijst".to_owned()));
+        assert_eq!(qualifiers[1].variant_group(), Some("rijst".to_owned()));
+        assert_eq!(qualifiers[2].variant_group(), None);
+        assert_eq!(qualifiers[0].to_string(), "variant thai curry of Rijst");
+    }
+}
@@ -0,0 +1,171 @@
+use std::fmt;
+
+use crate::qualifier::Qualifiers;
+
+/// Selection weight of a recipe which is picked more often
+pub const FAVOURITE_WEIGHT: f64 = 3.0;
+/// Selection weight of a recipe which is picked less often
+pub const RARELY_WEIGHT: f64 = 0.25;
+/// Selection weight of a regular recipe
+pub const DEFAULT_WEIGHT: f64 = 1.0;
+
+/// A single dish from the input, identified by `id` within one planner
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recipe {
+    id: usize,
+    name: String,
+    tags: Vec<String>,
+    source: Option<String>,
+    /// Preparation time in minutes
+    prep_time: Option<u32>,
+    servings: Option<u32>,
+    notes: Option<String>,
+    /// Relative chance of being picked, 1.0 being a regular recipe
+    weight: f64,
+    /// Meaning of the parentheses in the name, if they were interpreted. Boxed as most recipes
+    /// go without.
+    qualifiers: Option<Box<Qualifiers>>,
+}
+
+impl Recipe {
+    pub fn new(id: usize, name: String) -> Self {
+        Self {
+            id,
+            name,
+            tags: vec![],
+            source: None,
+            prep_time: None,
+            servings: None,
+            notes: None,
+            weight: DEFAULT_WEIGHT,
+            qualifiers: None,
+        }
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn tags(&self) -> &Vec<String> {
+        &self.tags
+    }
+
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    pub fn prep_time(&self) -> Option<u32> {
+        self.prep_time
+    }
+
+    pub fn servings(&self) -> Option<u32> {
+        self.servings
+    }
+
+    pub fn notes(&self) -> Option<&str> {
+        self.notes.as_deref()
+    }
+
+    pub fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    pub fn qualifiers(&self) -> Option<&Qualifiers> {
+        self.qualifiers.as_deref()
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    pub fn set_tags(&mut self, tags: Vec<String>) {
+        self.tags = tags;
+    }
+
+    pub fn set_source(&mut self, source: Option<String>) {
+        self.source = source;
+    }
+
+    pub fn set_prep_time(&mut self, prep_time: Option<u32>) {
+        self.prep_time = prep_time;
+    }
+
+    pub fn set_servings(&mut self, servings: Option<u32>) {
+        self.servings = servings;
+    }
+
+    pub fn set_notes(&mut self, notes: Option<String>) {
+        self.notes = notes;
+    }
+
+    pub fn set_weight(&mut self, weight: f64) {
+        self.weight = weight;
+    }
+
+    pub fn set_qualifiers(&mut self, qualifiers: Option<Qualifiers>) {
+        self.qualifiers = qualifiers.map(Box::new);
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+}
+
+impl fmt::Display for Recipe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Parses a weight given as `favourite`, `rarely`, `normal` or a non-negative number
+pub fn parse_weight(value: &str) -> Option<f64> {
+    match value.to_lowercase().as_str() {
+        "favourite" | "favorite" => Some(FAVOURITE_WEIGHT),
+        "rarely" => Some(RARELY_WEIGHT),
+        "normal" => Some(DEFAULT_WEIGHT),
+        number => number
+            .parse::<f64>()
+            .ok()
+            .filter(|w| w.is_finite() && *w >= 0.0),
+    }
+}
+
+/// Parses a comma separated list of tags, e.g. `vegetarian, quick`
+pub fn parse_tags(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Parses a preparation time in minutes given as `20min`, `20m`, `1h` or `20`
+pub fn parse_prep_time(value: &str) -> Option<u32> {
+    let value = value.to_lowercase();
+    if let Some(hours) = value.strip_suffix('h') {
+        return hours.parse::<u32>().ok()?.checked_mul(60);
+    }
+    let minutes = value
+        .strip_suffix("min")
+        .or_else(|| value.strip_suffix('m'))
+        .unwrap_or(&value);
+    minutes.parse().ok()
+}
+
+/// Formats a weight the way `parse_weight` reads it
+pub fn weight_label(weight: f64) -> String {
+    if weight == FAVOURITE_WEIGHT {
+        "favourite".to_owned()
+    } else if weight == RARELY_WEIGHT {
+        "rarely".to_owned()
+    } else if weight == DEFAULT_WEIGHT {
+        "normal".to_owned()
+    } else {
+        weight.to_string()
+    }
+}
@@ -0,0 +1,424 @@
+use dinner_generator::generate::Generate;
+use dinner_generator::history::DATE_FORMAT;
+use dinner_generator::recipe::{self, Recipe};
+use serde::Serialize;
+
+use crate::args::Format;
+
+/// Which optional columns to include
+#[derive(Clone, Copy)]
+pub struct Columns {
+    pub links: bool,
+    pub tags: bool,
+}
+
+/// A planned day or pool recipe as printed by `show`
+#[derive(Serialize)]
+struct Row {
+    /// Date of a day, None for pool recipes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    weekday: Option<String>,
+    entry: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    locked: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    weight: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    link: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tags: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+struct Plan {
+    /// Seed stored in the output file, next to the days which are rendered as stored rather
+    /// than topped up with new picks
+    seed: u64,
+    days: Vec<Row>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pool: Option<Vec<Row>>,
+}
+
+impl Row {
+    fn new(entry: String, recipe: Option<&Recipe>, columns: Columns) -> Self {
+        Self {
+            date: None,
+            weekday: None,
+            entry,
+            locked: None,
+            weight: None,
+            link: columns
+                .links
+                .then(|| recipe.and_then(|r| r.source()).unwrap_or("").to_owned()),
+            tags: columns
+                .tags
+                .then(|| recipe.map(|r| r.tags().clone()).unwrap_or_default()),
+        }
+    }
+
+    /// Values of the optional columns, in the order of `optional_headers`
+    fn optional_cells(&self) -> Vec<String> {
+        let mut cells = vec![];
+        if let Some(link) = &self.link {
+            cells.push(link.to_owned());
+        }
+        if let Some(tags) = &self.tags {
+            cells.push(tags.join(", "));
+        }
+        cells
+    }
+}
+
+fn optional_headers(columns: Columns) -> Vec<&'static str> {
+    let mut headers = vec![];
+    if columns.links {
+        headers.push("Link");
+    }
+    if columns.tags {
+        headers.push("Tags");
+    }
+    headers
+}
+
+fn day_rows(generate: &Generate, columns: Columns) -> Vec<Row> {
+    generate
+        .days()
+        .iter()
+        .enumerate()
+        .map(|(index, slot)| {
+            let date = generate.date_of(index);
+            Row {
+                date: Some(date.format(DATE_FORMAT).to_string()),
+                weekday: Some(date.format("%a").to_string()),
+                locked: Some(generate.is_locked(index)),
+                ..Row::new(slot.to_string(), slot.recipe(), columns)
+            }
+        })
+        .collect()
+}
+
+fn pool_rows(generate: &Generate, columns: Columns) -> Vec<Row> {
+    generate
+        .pool()
+        .iter()
+        .map(|recipe| Row {
+            weight: Some(recipe::weight_label(recipe.weight())),
+            ..Row::new(recipe.name().to_owned(), Some(recipe), columns)
+        })
+        .collect()
+}
+
+/// Renders the plan, and the pool if asked for, in the given format
+pub fn render(generate: &Generate, format: Format, pool: bool, columns: Columns) -> String {
+    let days = day_rows(generate, columns);
+    let pool = pool.then(|| pool_rows(generate, columns));
+    match format {
+        Format::Table => table(&days, pool.as_deref(), columns),
+        Format::Json => {
+            let plan = Plan {
+                seed: generate.seed(),
+                days,
+                pool,
+            };
+            let mut json = serde_json::to_string_pretty(&plan).unwrap_or_default();
+            json.push('\n');
+            json
+        }
+        Format::Csv => csv(&days, pool.as_deref(), columns),
+        Format::Markdown => markdown(&days, pool.as_deref(), columns),
+        Format::Plain => plain(&days, pool.as_deref()),
+    }
+}
+
+fn day_cells(row: &Row) -> Vec<String> {
+    let mut cells = vec![
+        row.date.clone().unwrap_or_default(),
+        row.weekday.clone().unwrap_or_default(),
+        if row.locked == Some(true) {
+            format!("{} (locked)", row.entry)
+        } else {
+            row.entry.clone()
+        },
+    ];
+    cells.extend(row.optional_cells());
+    cells
+}
+
+fn pool_cells(row: &Row) -> Vec<String> {
+    let mut cells = vec![row.entry.clone(), row.weight.clone().unwrap_or_default()];
+    cells.extend(row.optional_cells());
+    cells
+}
+
+fn headers(first: &[&'static str], columns: Columns) -> Vec<String> {
+    first
+        .iter()
+        .chain(optional_headers(columns).iter())
+        .map(|header| header.to_string())
+        .collect()
+}
+
+fn table(days: &[Row], pool: Option<&[Row]>, columns: Columns) -> String {
+    let mut out = aligned(
+        headers(&["Date", "Day", "Entry"], columns),
+        days.iter().map(day_cells).collect(),
+    );
+    if let Some(pool) = pool {
+        out.push('\n');
+        out.push_str(&aligned(
+            headers(&["Pool", "Weight"], columns),
+            pool.iter().map(pool_cells).collect(),
+        ));
+    }
+    out
+}
+
+/// Pads all cells to the widest cell of their column
+fn aligned(headers: Vec<String>, rows: Vec<Vec<String>>) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut out = String::new();
+    for row in std::iter::once(&headers).chain(rows.iter()) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        out.push_str(line.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Lists the recipes next to the interpretation of their parentheses, disabled ones marked
+/// with a `#`
+pub fn lint(recipes: &[Recipe], archive: &[Recipe]) -> String {
+    let rows = recipes
+        .iter()
+        .map(|recipe| (recipe.name().to_owned(), recipe))
+        .chain(
+            archive
+                .iter()
+                .map(|recipe| (format!("#{}", recipe.name()), recipe)),
+        )
+        .map(|(entry, recipe)| {
+            let meaning = recipe
+                .qualifiers()
+                .map_or("plain name".to_owned(), |q| q.to_string());
+            vec![entry, meaning]
+        })
+        .collect();
+    aligned(vec!["Entry".to_owned(), "Read as".to_owned()], rows)
+}
+
+fn csv(days: &[Row], pool: Option<&[Row]>, columns: Columns) -> String {
+    let header = headers(&["List", "Date", "Weekday", "Entry", "Weight"], columns);
+    let mut lines = vec![header.iter().map(|h| h.to_lowercase()).collect()];
+    for row in days {
+        let mut cells = vec![
+            "day".to_owned(),
+            row.date.clone().unwrap_or_default(),
+            row.weekday.clone().unwrap_or_default(),
+            row.entry.clone(),
+            String::new(),
+        ];
+        cells.extend(row.optional_cells());
+        lines.push(cells);
+    }
+    for row in pool.unwrap_or_default() {
+        let mut cells = vec!["pool".to_owned(), String::new(), String::new()];
+        cells.extend(pool_cells(row));
+        lines.push(cells);
+    }
+    lines
+        .iter()
+        .map(|cells| {
+            let cells: Vec<String> = cells.iter().map(|cell| csv_field(cell)).collect();
+            format!("{}\n", cells.join(","))
+        })
+        .collect()
+}
+
+/// Quotes a CSV field if it contains a separator, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+fn markdown(days: &[Row], pool: Option<&[Row]>, columns: Columns) -> String {
+    let mut out = markdown_table(
+        headers(&["Date", "Day", "Entry"], columns),
+        days.iter().map(day_cells).collect(),
+    );
+    if let Some(pool) = pool {
+        out.push('\n');
+        out.push_str(&markdown_table(
+            headers(&["Pool", "Weight"], columns),
+            pool.iter().map(pool_cells).collect(),
+        ));
+    }
+    out
+}
+
+fn markdown_table(headers: Vec<String>, rows: Vec<Vec<String>>) -> String {
+    let mut out = format!("| {} |\n", headers.join(" | "));
+    out.push_str(&format!("|{}\n", "---|".repeat(headers.len())));
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|cell| cell.replace('|', "\\|")).collect();
+        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    out
+}
+
+fn plain(days: &[Row], pool: Option<&[Row]>) -> String {
+    let mut out = String::new();
+    for row in days {
+        let mut cells = vec![row.date.clone().unwrap_or_default(), row.entry.clone()];
+        cells.extend(row.optional_cells());
+        out.push_str(&format!("{}\n", cells.join("\t")));
+    }
+    for row in pool.unwrap_or_default() {
+        let mut cells = vec!["pool".to_owned(), row.entry.clone()];
+        cells.extend(row.optional_cells());
+        out.push_str(&format!("{}\n", cells.join("\t")));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dinner_generator::settings::Settings;
+
+    fn planner() -> Generate {
+        let mut soep = Recipe::new(0, "Soep | brood".to_owned());
+        soep.set_source(Some("https://example.com/soep?a=1,2".to_owned()));
+        let mut settings = Settings::default();
+        settings.set_horizon(1);
+        let mut generate = Generate::from_recipes(vec![soep], settings);
+        generate.set_seed(1);
+        let mut generate = generate.generate_days(false);
+        generate.set_locked(0, true).unwrap();
+        generate
+            .add_to_pool("Pasta \"pesto\", basil [quick, vegetarian] *2".to_owned())
+            .unwrap();
+        generate
+    }
+
+    const ALL: Columns = Columns {
+        links: true,
+        tags: true,
+    };
+
+    /// Date and weekday of the single planned day, as rendered
+    fn day(generate: &Generate) -> (String, String) {
+        let date = generate.date_of(0);
+        (
+            date.format(DATE_FORMAT).to_string(),
+            date.format("%a").to_string(),
+        )
+    }
+
+    #[test]
+    fn table_aligns_columns() {
+        let generate = planner();
+        let (date, weekday) = day(&generate);
+
+        assert_eq!(
+            render(&generate, Format::Table, true, ALL),
+            format!(
+                "Date        Day  Entry                  Link                            Tags\n\
+                 {date}  {weekday}  Soep | brood (locked)  https://example.com/soep?a=1,2\n\
+                 \n\
+                 Pool                  Weight  Link  Tags\n\
+                 Pasta \"pesto\", basil  2             quick, vegetarian\n"
+            )
+        );
+        let columns = Columns {
+            links: false,
+            tags: false,
+        };
+        assert_eq!(
+            render(&generate, Format::Table, false, columns),
+            format!("Date        Day  Entry\n{date}  {weekday}  Soep | brood (locked)\n")
+        );
+    }
+
+    #[test]
+    fn json_ends_with_newline() {
+        let generate = planner();
+        let json = render(&generate, Format::Json, true, ALL);
+        let plan: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert!(json.ends_with("}\n"));
+        assert_eq!(plan["seed"], 1);
+        assert_eq!(plan["days"][0]["entry"], "Soep | brood");
+        assert_eq!(plan["days"][0]["locked"], true);
+        assert_eq!(plan["pool"][0]["entry"], "Pasta \"pesto\", basil");
+        assert_eq!(plan["pool"][0]["tags"][1], "vegetarian");
+        assert!(plan["pool"][0].get("date").is_none());
+
+        let json = render(&generate, Format::Json, false, ALL);
+        let plan: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(json.ends_with("}\n"));
+        assert!(plan.get("pool").is_none());
+    }
+
+    #[test]
+    fn csv_quotes_separators_and_quotes() {
+        let generate = planner();
+        let (date, weekday) = day(&generate);
+
+        assert_eq!(
+            render(&generate, Format::Csv, true, ALL),
+            format!(
+                "list,date,weekday,entry,weight,link,tags\n\
+                 day,{date},{weekday},Soep | brood,,\"https://example.com/soep?a=1,2\",\n\
+                 pool,,,\"Pasta \"\"pesto\"\", basil\",2,,\"quick, vegetarian\"\n"
+            )
+        );
+        assert_eq!(csv_field("a\nb"), "\"a\nb\"");
+    }
+
+    #[test]
+    fn markdown_escapes_pipes() {
+        let generate = planner();
+        let (date, weekday) = day(&generate);
+
+        assert_eq!(
+            render(&generate, Format::Markdown, true, ALL),
+            format!(
+                "| Date | Day | Entry | Link | Tags |\n\
+                 |---|---|---|---|---|\n\
+                 | {date} | {weekday} | Soep \\| brood (locked) | https://example.com/soep?a=1,2 |  |\n\
+                 \n\
+                 | Pool | Weight | Link | Tags |\n\
+                 |---|---|---|---|\n\
+                 | Pasta \"pesto\", basil | 2 |  | quick, vegetarian |\n"
+            )
+        );
+    }
+
+    #[test]
+    fn plain_separates_with_tabs() {
+        let generate = planner();
+        let (date, _) = day(&generate);
+
+        assert_eq!(
+            render(&generate, Format::Plain, true, ALL),
+            format!(
+                "{date}\tSoep | brood\thttps://example.com/soep?a=1,2\t\n\
+                 pool\tPasta \"pesto\", basil\t\tquick, vegetarian\n"
+            )
+        );
+    }
+}
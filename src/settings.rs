@@ -0,0 +1,113 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// Amount of days planned ahead by default
+pub const DEFAULT_HORIZON: usize = 7;
+/// Amount of days a planned recipe is excluded from selection, one week by default so recipes
+/// do not repeat two weeks in a row
+pub const DEFAULT_COOLDOWN_DAYS: i64 = 7;
+
+/// How far ahead and on which days recipes are planned
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Amount of planned days, days on weekdays which are not planned do not count
+    horizon: usize,
+    /// Day on which a new week starts when listing the days
+    week_start: Weekday,
+    /// Weekdays to plan a recipe for, the other days are skipped
+    weekdays: Vec<Weekday>,
+    /// Whether parentheses in recipe names are read as seasons, optional components and
+    /// variants when loading the input
+    qualifiers: bool,
+    /// Amount of days a planned recipe is not picked again, unless the pool runs short
+    cooldown_days: i64,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            horizon: DEFAULT_HORIZON,
+            week_start: Weekday::Mon,
+            weekdays: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+                Weekday::Sat,
+                Weekday::Sun,
+            ],
+            qualifiers: false,
+            cooldown_days: DEFAULT_COOLDOWN_DAYS,
+        }
+    }
+}
+
+impl Settings {
+    pub fn horizon(&self) -> usize {
+        self.horizon
+    }
+
+    pub fn set_horizon(&mut self, horizon: usize) {
+        self.horizon = horizon;
+    }
+
+    pub fn week_start(&self) -> Weekday {
+        self.week_start
+    }
+
+    pub fn set_week_start(&mut self, week_start: Weekday) {
+        self.week_start = week_start;
+    }
+
+    pub fn weekdays(&self) -> &Vec<Weekday> {
+        &self.weekdays
+    }
+
+    pub fn set_weekdays(&mut self, weekdays: Vec<Weekday>) {
+        self.weekdays = weekdays;
+    }
+
+    pub fn qualifiers(&self) -> bool {
+        self.qualifiers
+    }
+
+    pub fn set_qualifiers(&mut self, qualifiers: bool) {
+        self.qualifiers = qualifiers;
+    }
+
+    pub fn cooldown_days(&self) -> i64 {
+        self.cooldown_days
+    }
+
+    pub fn set_cooldown_days(&mut self, cooldown_days: i64) {
+        self.cooldown_days = cooldown_days;
+    }
+
+    /// Whether a recipe is planned for the given date
+    pub fn plans(&self, date: NaiveDate) -> bool {
+        self.weekdays.contains(&date.weekday())
+    }
+
+    /// Whether the given date starts a new week
+    pub fn starts_week(&self, date: NaiveDate) -> bool {
+        date.weekday() == self.week_start
+    }
+
+    /// Amount of calendar days from `start` needed to cover the horizon
+    pub fn plan_length(&self, start: NaiveDate) -> usize {
+        if self.weekdays.is_empty() {
+            return 0;
+        }
+        let mut planned = 0;
+        let mut length = 0;
+        while planned < self.horizon {
+            if self.plans(start + Duration::days(length as i64)) {
+                planned += 1;
+            }
+            length += 1;
+        }
+        length
+    }
+}
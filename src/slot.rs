@@ -0,0 +1,98 @@
+use std::fmt;
+
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+
+use crate::recipe::Recipe;
+
+/// What is planned for a single day
+#[derive(Debug, Clone, PartialEq)]
+pub enum Slot {
+    /// A recipe is cooked
+    Planned(Recipe),
+    /// No dinner is planned
+    Skip,
+    /// Dinner is eaten somewhere else
+    EatOut,
+    /// Leftovers of an earlier day are eaten
+    Leftovers,
+    /// Nothing decided yet, filled in by generating
+    Unplanned,
+}
+
+/// The kinds of slots which do not hold a recipe
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum SlotKind {
+    /// See [`Slot::Skip`]
+    Skip,
+    /// See [`Slot::EatOut`]
+    EatOut,
+    /// See [`Slot::Leftovers`]
+    Leftovers,
+    /// See [`Slot::Unplanned`]
+    Unplanned,
+}
+
+impl Slot {
+    pub fn recipe(&self) -> Option<&Recipe> {
+        match self {
+            Slot::Planned(recipe) => Some(recipe),
+            _ => None,
+        }
+    }
+
+    pub fn is_unplanned(&self) -> bool {
+        matches!(self, Slot::Unplanned)
+    }
+
+    /// Days set aside for something other than a recipe, which generating leaves alone
+    pub fn is_reserved(&self) -> bool {
+        matches!(self, Slot::Skip | Slot::EatOut | Slot::Leftovers)
+    }
+
+    /// Line written to the output file, recipes are stored by name and other slots as a marker
+    pub fn to_line(&self) -> String {
+        match self {
+            Slot::Planned(recipe) => recipe.name().to_owned(),
+            Slot::Skip => "[skip]".to_owned(),
+            Slot::EatOut => "[eat out]".to_owned(),
+            Slot::Leftovers => "[leftovers]".to_owned(),
+            Slot::Unplanned => "[unplanned]".to_owned(),
+        }
+    }
+
+    /// Reads a line written by `to_line`, returns None if it holds a recipe name
+    pub fn from_marker(line: &str) -> Option<Self> {
+        match line {
+            "[skip]" => Some(Slot::Skip),
+            "[eat out]" => Some(Slot::EatOut),
+            "[leftovers]" => Some(Slot::Leftovers),
+            "[unplanned]" => Some(Slot::Unplanned),
+            _ => None,
+        }
+    }
+}
+
+impl From<SlotKind> for Slot {
+    fn from(kind: SlotKind) -> Self {
+        match kind {
+            SlotKind::Skip => Slot::Skip,
+            SlotKind::EatOut => Slot::EatOut,
+            SlotKind::Leftovers => Slot::Leftovers,
+            SlotKind::Unplanned => Slot::Unplanned,
+        }
+    }
+}
+
+impl fmt::Display for Slot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Slot::Planned(recipe) => write!(f, "{}", recipe),
+            Slot::Skip => write!(f, "Skipped"),
+            Slot::EatOut => write!(f, "Eating out"),
+            Slot::Leftovers => write!(f, "Leftovers"),
+            Slot::Unplanned => write!(f, "Unplanned"),
+        }
+    }
+}
@@ -0,0 +1,558 @@
+use dinner_generator::config::Config;
+use dinner_generator::error::{Error, Result};
+use dinner_generator::generate::{Generate, Strategy};
+use dinner_generator::matching;
+use dinner_generator::recipe::{self, Recipe, DEFAULT_WEIGHT, FAVOURITE_WEIGHT, RARELY_WEIGHT};
+use dinner_generator::slot::SlotKind;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Clear, List, ListItem, ListState, Paragraph, Tabs, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::time::Duration;
+
+/// How often the days are checked for having passed while waiting for a key
+const TICK: Duration = Duration::from_secs(60);
+
+#[derive(Eq, PartialEq, Clone, Copy)]
+enum ActiveView {
+    Days,
+    Pool,
+    Archive,
+}
+
+const VIEWS: [(ActiveView, &str); 3] = [
+    (ActiveView::Days, "Days"),
+    (ActiveView::Pool, "Pool"),
+    (ActiveView::Archive, "Archived"),
+];
+
+/// What the text typed at the prompt is for
+#[derive(Clone, Copy)]
+enum Prompt {
+    EditDay(usize),
+    AddToPool,
+    EditPoolEntry(usize),
+    EditTags(usize),
+    Search,
+}
+
+enum Mode {
+    Normal,
+    Input(Prompt, String),
+    /// The value typed for a day only resembles pool recipes: the day, the value as typed, the
+    /// matches and the highlighted match
+    ChooseRecipe(usize, String, Vec<(usize, String)>, usize),
+}
+
+/// Full-screen terminal frontend, offering the same actions as the window
+pub struct TerminalViewer {
+    entries: Generate,
+    config: Config,
+    active_view: ActiveView,
+    mode: Mode,
+    /// Highlighted row of each view
+    selected: [usize; 3],
+    /// Day selected as the first of a swap
+    swapping: Option<usize>,
+    /// Filter of the pool and archive lists
+    search: String,
+    /// Last error, shown until the next key
+    error: Option<String>,
+    quit: bool,
+}
+
+impl TerminalViewer {
+    pub fn new(entries: Generate, config: Config) -> Self {
+        TerminalViewer {
+            entries,
+            config,
+            active_view: ActiveView::Days,
+            mode: Mode::Normal,
+            selected: [0; 3],
+            swapping: None,
+            search: String::new(),
+            error: None,
+            quit: false,
+        }
+    }
+
+    /// Keeps the error of a failed action to show it
+    fn report(&mut self, result: Result<()>) {
+        if let Err(e) = result {
+            self.error = Some(e.to_string());
+        }
+    }
+
+    fn save_days(&mut self) {
+        let result = self.entries.write_file(self.config.output_file());
+        self.report(result);
+    }
+
+    /// Recipes of the active view, if it lists recipes
+    fn recipes(&self) -> &[Recipe] {
+        match self.active_view {
+            ActiveView::Days => &[],
+            ActiveView::Pool => self.entries.pool(),
+            ActiveView::Archive => self.entries.archive(),
+        }
+    }
+
+    /// Indices of the rows of the active view, filtered by the search for the pool and archive
+    fn listed(&self) -> Vec<usize> {
+        if self.active_view == ActiveView::Days {
+            return (0..self.entries.days().len()).collect();
+        }
+        let recipes = self.recipes();
+        if self.search.trim().is_empty() {
+            return (0..recipes.len()).collect();
+        }
+        matching::search(&self.search, recipes.iter().map(|r| r.name()))
+    }
+
+    /// Index in the days, pool or archive of the highlighted row
+    fn current(&self) -> Option<usize> {
+        self.listed()
+            .get(self.selected[self.active_view as usize])
+            .copied()
+    }
+
+    fn move_selection(&mut self, up: bool) {
+        let rows = self.listed().len();
+        let selected = &mut self.selected[self.active_view as usize];
+        *selected = if up {
+            selected.saturating_sub(1)
+        } else {
+            (*selected + 1).min(rows.saturating_sub(1))
+        };
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        while !self.quit {
+            if self.entries.rotate() > 0 {
+                self.entries.fill_unplanned();
+                self.save_days();
+            }
+            terminal
+                .draw(|frame| self.draw(frame))
+                .map_err(Error::io("terminal"))?;
+            if !event::poll(TICK).map_err(Error::io("terminal"))? {
+                continue;
+            }
+            if let Event::Key(key) = event::read().map_err(Error::io("terminal"))? {
+                if key.kind == KeyEventKind::Press {
+                    self.error = None;
+                    self.handle_key(key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::Normal => self.handle_normal_key(key.code),
+            Mode::Input(prompt, text) => self.handle_input_key(key.code, prompt, text),
+            Mode::ChooseRecipe(day, typed, candidates, selected) => {
+                self.handle_choose_key(key.code, day, typed, candidates, selected)
+            }
+        }
+    }
+
+    fn handle_normal_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Tab => {
+                let next = VIEWS.iter().position(|(view, _)| *view == self.active_view);
+                self.active_view = VIEWS[next.map_or(0, |i| (i + 1) % VIEWS.len())].0;
+            }
+            KeyCode::Char('1') => self.active_view = ActiveView::Days,
+            KeyCode::Char('2') => self.active_view = ActiveView::Pool,
+            KeyCode::Char('3') => self.active_view = ActiveView::Archive,
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(true),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(false),
+            code => match self.active_view {
+                ActiveView::Days => self.handle_days_key(code),
+                ActiveView::Pool => self.handle_pool_key(code),
+                ActiveView::Archive => self.handle_archive_key(code),
+            },
+        }
+    }
+
+    fn handle_days_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char('G') => {
+                self.entries = self.entries.generate_days(true);
+                self.save_days();
+            }
+            KeyCode::Char('f') => {
+                self.entries.fill_unplanned();
+                self.save_days();
+            }
+            KeyCode::Char('S') => {
+                self.entries.randomize_days(true, true);
+                self.save_days();
+            }
+            KeyCode::Char('t') => {
+                let strategy = match self.entries.strategy() {
+                    Strategy::Random => Strategy::Recency,
+                    Strategy::Recency => Strategy::Random,
+                };
+                self.entries.set_strategy(strategy);
+            }
+            code => {
+                let Some(index) = self.current() else {
+                    return;
+                };
+                self.handle_day_key(code, index);
+            }
+        }
+    }
+
+    /// Actions on the highlighted day
+    fn handle_day_key(&mut self, code: KeyCode, index: usize) {
+        let result = match code {
+            KeyCode::Char('r') => self.entries.regenerate_entry(index),
+            KeyCode::Char('e') | KeyCode::Enter => {
+                let day = &self.entries.days()[index];
+                let text = day.recipe().map_or(String::new(), |r| r.name().to_owned());
+                self.mode = Mode::Input(Prompt::EditDay(index), text);
+                return;
+            }
+            KeyCode::Char('s') => match self.swapping.take() {
+                None => {
+                    self.swapping = Some(index);
+                    return;
+                }
+                Some(first_index) => self.entries.swap_days_entries(first_index, index),
+            },
+            KeyCode::Char('l') => {
+                let locked = self.entries.is_locked(index);
+                self.entries.set_locked(index, !locked)
+            }
+            KeyCode::Char('x') => self.entries.set_day_slot(index, SlotKind::Skip),
+            KeyCode::Char('o') => self.entries.set_day_slot(index, SlotKind::EatOut),
+            KeyCode::Char('v') => self.entries.set_day_slot(index, SlotKind::Leftovers),
+            KeyCode::Char('u') => self.entries.set_day_slot(index, SlotKind::Unplanned),
+            _ => return,
+        };
+        self.report(result);
+        self.save_days();
+    }
+
+    fn handle_pool_key(&mut self, code: KeyCode) {
+        if code == KeyCode::Char('/') {
+            self.mode = Mode::Input(Prompt::Search, self.search.clone());
+            return;
+        }
+        if code == KeyCode::Char('a') {
+            self.mode = Mode::Input(Prompt::AddToPool, String::new());
+            return;
+        }
+        let Some(index) = self.current() else {
+            return;
+        };
+        let result = match code {
+            KeyCode::Char('e') | KeyCode::Enter => {
+                let name = self.entries.pool()[index].name().to_owned();
+                self.mode = Mode::Input(Prompt::EditPoolEntry(index), name);
+                return;
+            }
+            KeyCode::Char('t') => {
+                let tags = self.entries.pool()[index].tags().join(", ");
+                self.mode = Mode::Input(Prompt::EditTags(index), tags);
+                return;
+            }
+            KeyCode::Char('d') => self.entries.remove_from_pool(index),
+            KeyCode::Char('w') => {
+                let weight = match self.entries.pool()[index].weight() {
+                    weight if weight == DEFAULT_WEIGHT => FAVOURITE_WEIGHT,
+                    weight if weight == FAVOURITE_WEIGHT => RARELY_WEIGHT,
+                    _ => DEFAULT_WEIGHT,
+                };
+                self.entries.set_pool_weight(index, weight)
+            }
+            _ => return,
+        };
+        self.report(result);
+        self.move_selection(true);
+        self.move_selection(false);
+    }
+
+    fn handle_archive_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char('/') => self.mode = Mode::Input(Prompt::Search, self.search.clone()),
+            KeyCode::Char('r') | KeyCode::Enter => {
+                if let Some(index) = self.current() {
+                    let result = self.entries.restore_from_archive(index);
+                    self.report(result);
+                    self.move_selection(true);
+                    self.move_selection(false);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn handle_input_key(&mut self, code: KeyCode, prompt: Prompt, mut text: String) {
+        match code {
+            KeyCode::Esc => {
+                if let Prompt::Search = prompt {
+                    self.search.clear();
+                }
+                return;
+            }
+            KeyCode::Enter => {
+                self.submit(prompt, text);
+                return;
+            }
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Char(c) => text.push(c),
+            _ => (),
+        }
+        if let Prompt::Search = prompt {
+            self.search = text.clone();
+            self.selected[self.active_view as usize] = 0;
+        }
+        self.mode = Mode::Input(prompt, text);
+    }
+
+    fn submit(&mut self, prompt: Prompt, text: String) {
+        if text.is_empty() && !matches!(prompt, Prompt::EditTags(_)) {
+            return;
+        }
+        let result = match prompt {
+            Prompt::EditDay(index) => match self.entries.plan_entry(index, &text) {
+                Err(Error::Ambiguous { candidates, .. }) => {
+                    self.mode = Mode::ChooseRecipe(index, text, candidates, 0);
+                    return;
+                }
+                planned => planned,
+            }
+            .and_then(|_| self.entries.write_file(self.config.output_file())),
+            Prompt::AddToPool => self.entries.add_to_pool(text),
+            Prompt::EditPoolEntry(index) => self.entries.edit_pool_entry(index, text),
+            Prompt::EditTags(index) => self.entries.set_pool_tags(index, recipe::parse_tags(&text)),
+            Prompt::Search => return,
+        };
+        self.report(result);
+    }
+
+    fn handle_choose_key(
+        &mut self,
+        code: KeyCode,
+        day: usize,
+        typed: String,
+        candidates: Vec<(usize, String)>,
+        selected: usize,
+    ) {
+        // The value as typed is offered below the matches
+        let result = match code {
+            KeyCode::Esc => return,
+            KeyCode::Enter => match candidates.get(selected) {
+                Some(&(pos, _)) => self.entries.plan_pool_entry(day, pos),
+                None => self.entries.edit_days_entry(day, typed),
+            },
+            KeyCode::Up | KeyCode::Char('k') => {
+                let selected = selected.saturating_sub(1);
+                self.mode = Mode::ChooseRecipe(day, typed, candidates, selected);
+                return;
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                let selected = (selected + 1).min(candidates.len());
+                self.mode = Mode::ChooseRecipe(day, typed, candidates, selected);
+                return;
+            }
+            _ => {
+                self.mode = Mode::ChooseRecipe(day, typed, candidates, selected);
+                return;
+            }
+        };
+        self.report(result);
+        self.save_days();
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [tabs, list, status, help] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(3),
+            Constraint::Length(1),
+            Constraint::Length(2),
+        ])
+        .areas(frame.area());
+
+        let active = VIEWS.iter().position(|(view, _)| *view == self.active_view);
+        frame.render_widget(
+            Tabs::new(VIEWS.iter().map(|(_, label)| *label))
+                .select(active)
+                .highlight_style(Style::new().bold().reversed()),
+            tabs,
+        );
+
+        let items = match self.active_view {
+            ActiveView::Days => self.day_items(),
+            ActiveView::Pool | ActiveView::Archive => self.recipe_items(),
+        };
+        let mut state =
+            ListState::default().with_selected(Some(self.selected[self.active_view as usize]));
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(Block::bordered().title("dinner viewer"))
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+            list,
+            &mut state,
+        );
+
+        frame.render_widget(Paragraph::new(self.status_line()), status);
+        frame.render_widget(
+            Paragraph::new(self.help_line())
+                .wrap(Wrap { trim: true })
+                .dim(),
+            help,
+        );
+
+        if let Mode::ChooseRecipe(_, typed, candidates, selected) = &self.mode {
+            self.draw_choice(frame, typed, candidates, *selected);
+        }
+    }
+
+    fn day_items(&self) -> Vec<ListItem<'static>> {
+        self.entries
+            .days()
+            .iter()
+            .enumerate()
+            .map(|(index, day)| {
+                let date = self.entries.date_of(index);
+                let mut label = format!("{}: {}", date.format("%a %-d %b"), day);
+                if self.entries.is_locked(index) {
+                    label = format!("🔒 {}", label);
+                }
+                if self.swapping == Some(index) {
+                    label = format!("{} (swapping)", label);
+                }
+                let item = ListItem::new(label);
+                // Underlined days start a new week
+                if index > 0 && self.entries.settings().starts_week(date) {
+                    item.underlined()
+                } else {
+                    item
+                }
+            })
+            .collect()
+    }
+
+    fn recipe_items(&self) -> Vec<ListItem<'static>> {
+        let recipes = self.recipes();
+        self.listed()
+            .into_iter()
+            .map(|index| {
+                let recipe = &recipes[index];
+                let mut label = recipe.name().to_owned();
+                if !recipe.tags().is_empty() {
+                    label = format!("{} [{}]", label, recipe.tags().join(", "));
+                }
+                if self.active_view == ActiveView::Pool {
+                    label = format!("{} ({})", label, recipe::weight_label(recipe.weight()));
+                }
+                ListItem::new(label)
+            })
+            .collect()
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        if let Some(error) = &self.error {
+            return Line::from(error.clone()).red();
+        }
+        match &self.mode {
+            Mode::Input(prompt, text) => {
+                let label = match prompt {
+                    Prompt::EditDay(_) => "Edit day entry",
+                    Prompt::AddToPool => "Add entry to pool",
+                    Prompt::EditPoolEntry(_) => "Edit pool entry",
+                    Prompt::EditTags(_) => "Tags, separated by commas",
+                    Prompt::Search => "Search",
+                };
+                Line::from(format!("{}: {}▏", label, text))
+            }
+            _ if !self.search.is_empty() && self.active_view != ActiveView::Days => {
+                Line::from(format!("Search: {}", self.search))
+            }
+            _ if !self.entries.relaxed().is_empty() => Line::from(format!(
+                "Cooldown relaxed for: {}",
+                self.entries.relaxed().join(", ")
+            )),
+            _ => Line::from(format!(
+                "seed: {}, strategy: {:?}",
+                self.entries.seed(),
+                self.entries.strategy()
+            )),
+        }
+    }
+
+    fn help_line(&self) -> &'static str {
+        match (&self.mode, self.active_view) {
+            (Mode::Input(..), _) => "enter: submit  esc: cancel",
+            (Mode::ChooseRecipe(..), _) => "↑↓: choose  enter: plan  esc: cancel",
+            (Mode::Normal, ActiveView::Days) => {
+                "r: regenerate  e: edit  s: swap  l: lock  x/o/v/u: skip/eat out/leftovers/unplanned  G: regenerate all  f: fill  S: shuffle  t: strategy  tab: view  q: quit"
+            }
+            (Mode::Normal, ActiveView::Pool) => {
+                "a: add  e: edit  t: tags  d: remove  w: weight  /: search  tab: view  q: quit"
+            }
+            (Mode::Normal, ActiveView::Archive) => "r: restore  /: search  tab: view  q: quit",
+        }
+    }
+
+    /// Popup listing the pool recipes matching the value typed for a day
+    fn draw_choice(
+        &self,
+        frame: &mut Frame,
+        typed: &str,
+        candidates: &[(usize, String)],
+        selected: usize,
+    ) {
+        let mut items: Vec<ListItem> = candidates
+            .iter()
+            .map(|(_, name)| ListItem::new(name.as_str()))
+            .collect();
+        items.push(ListItem::new(format!("Add \"{}\" as typed", typed)));
+        let area = frame.area();
+        let height = (items.len() as u16 + 2).min(area.height);
+        let popup = Rect {
+            x: area.width / 6,
+            y: area.height.saturating_sub(height) / 2,
+            width: area.width * 2 / 3,
+            height,
+        };
+        frame.render_widget(Clear, popup);
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(Block::bordered().title(format!("{} could mean", typed)))
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+            popup,
+            &mut ListState::default().with_selected(Some(selected)),
+        );
+    }
+}
+
+/// Opens the files from the config and runs the terminal frontend until it is quit
+pub fn run(config: Config) -> Result<()> {
+    config.prepare_files()?;
+    let mut entries = Generate::read_entries(
+        config.input_file(),
+        config.output_file(),
+        config.plan().clone(),
+        false,
+    )?;
+    entries.rotate();
+    entries.fill_unplanned();
+    entries.write_file(config.output_file())?;
+
+    let mut terminal = ratatui::try_init().map_err(Error::io("terminal"))?;
+    let result = TerminalViewer::new(entries, config).run(&mut terminal);
+    ratatui::restore();
+    result
+}